@@ -0,0 +1,192 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+use futures::{future, stream, Future, Stream};
+use serde::de::DeserializeOwned;
+use tokio::runtime::Runtime;
+
+use crate::transport::{ApiError, ApiRequest, ApiResponse, Transport};
+use crate::teamwork_service::{LoggedTime, Task, TaskList, TaskResponse, TaskTimeEntriesResponse, TasklistsResponse, TasksResponse, TimeEntriesResponse, TimeEntry};
+
+/// Requests in flight at the same time when none is configured.
+pub const DEFAULT_CONCURRENCY: usize = 8;
+const PAGE_SIZE: usize = 500;
+
+type Params = Vec<(String, String)>;
+type Fetch<T> = Box<dyn Future<Item = T, Error = ApiError> + Send>;
+/// Items read so far and the number of the last page read.
+type PageLoop<T> = future::Loop<Vec<T>, (Vec<T>, usize)>;
+
+/// Asynchronous client of the Teamwork API, for the requests which fan out.
+///
+/// Its clones share one connection pool, and at most `concurrency` requests are in flight
+/// for each call. Futures are run to completion with [`AsyncTeamWorkService::block_on`],
+/// on a runtime started on first use and kept alive with the pool.
+#[derive(Clone)]
+pub struct AsyncTeamWorkService {
+    transport: Arc<dyn Transport>,
+    runtime: Arc<Mutex<Option<Runtime>>>,
+    concurrency: usize,
+}
+
+impl AsyncTeamWorkService {
+    pub fn new(transport: Arc<dyn Transport>) -> AsyncTeamWorkService {
+        return AsyncTeamWorkService {
+            transport,
+            runtime: Arc::new(Mutex::new(None)),
+            concurrency: DEFAULT_CONCURRENCY,
+        };
+    }
+
+    pub fn with_concurrency(self, concurrency: usize) -> AsyncTeamWorkService {
+        return AsyncTeamWorkService {
+            concurrency: concurrency.max(1),
+            ..self
+        };
+    }
+
+    /// Runs a future of this client to completion, from blocking code.
+    pub fn block_on<T: Send + 'static>(&self, fetch: Fetch<T>) -> Result<T, ApiError> {
+        let mut runtime = self.runtime.lock().unwrap();
+        if runtime.is_none() {
+            *runtime = Some(Runtime::new().expect("Could not start the async runtime"));
+        }
+
+        return runtime.as_mut().unwrap().block_on(fetch);
+    }
+
+    /// Tasks by id, in the order of the ids. A task which could not be read does not fail the others.
+    pub fn get_tasks(&self, task_ids: Vec<usize>) -> Fetch<Vec<Result<Task, ApiError>>> {
+        let service = self.clone();
+        return self.for_each(task_ids, move |id| {
+            Box::new(service.get::<TaskResponse>(&format!("tasks/{}.json", id), vec![])
+                .map(|r| r.task)
+                .then(Ok))
+        });
+    }
+
+    /// Task lists of each project, in the order of the projects.
+    pub fn list_tasklists(&self, project_ids: Vec<String>) -> Fetch<Vec<Vec<TaskList>>> {
+        let service = self.clone();
+        return self.for_each(project_ids, move |id| {
+            Box::new(service.get::<TasklistsResponse>(&format!("projects/{}/tasklists.json", id), vec![])
+                .map(|r| r.tasklists))
+        });
+    }
+
+    /// Tasks of each task list, sub tasks nested, in the order of the task lists.
+    pub fn list_tasks(&self, tasklist_ids: Vec<String>) -> Fetch<Vec<Vec<Task>>> {
+        let service = self.clone();
+        return self.for_each(tasklist_ids, move |id| {
+            let params = vec![("nestSubTasks".to_string(), "yes".to_string())];
+            Box::new(service.get::<TasksResponse>(&format!("tasklists/{}/tasks.json", id), params)
+                .map(|r| r.tasks))
+        });
+    }
+
+    /// Time entries of everyone since `since_date`.
+    pub fn all_time_entries(&self, since_date: NaiveDate) -> Fetch<Vec<TimeEntry>> {
+        let params = vec![("fromdate".to_string(), since_date.format("%Y%m%d").to_string())];
+        return self.all_pages("time_entries.json", params, |r: TimeEntriesResponse| r.time_entries);
+    }
+
+    /// Every time entry of a task, by everyone.
+    pub fn task_logged_time(&self, task_id: &str) -> Fetch<Vec<LoggedTime>> {
+        let path = format!("tasks/{}/time_entries.json", task_id);
+        return self.all_pages(&path, vec![], |r: TaskTimeEntriesResponse| r.time_entries);
+    }
+
+    /// Every time entry of a project, by everyone.
+    pub fn project_logged_time(&self, project_id: &str) -> Fetch<Vec<LoggedTime>> {
+        let path = format!("projects/{}/time_entries.json", project_id);
+        return self.all_pages(&path, vec![], |r: TaskTimeEntriesResponse| r.time_entries);
+    }
+
+    /// Runs `fetch` for each key, `concurrency` at a time, keeping the order of the keys.
+    fn for_each<K, T, F>(&self, keys: Vec<K>, fetch: F) -> Fetch<Vec<T>>
+        where K: Send + 'static,
+              T: Send + 'static,
+              F: Fn(K) -> Fetch<T> + Send + 'static
+    {
+        return Box::new(stream::iter_ok(keys)
+            .map(fetch)
+            .buffered(self.concurrency)
+            .collect());
+    }
+
+    /// Reads the first page, then the others at once when Teamwork tells how many there are.
+    /// Without the `X-Pages` header, pages are read one after the other until one is not full.
+    fn all_pages<R, T, F>(&self, path: &str, params: Params, items: F) -> Fetch<Vec<T>>
+        where R: DeserializeOwned + Send + 'static,
+              T: Send + 'static,
+              F: Fn(R) -> Vec<T> + Copy + Send + 'static
+    {
+        let service = self.clone();
+        let path = path.to_string();
+        let page = move |n: usize| -> Fetch<(Vec<T>, Option<usize>)> {
+            let mut page_params = params.clone();
+            page_params.push(("page".to_string(), n.to_string()));
+            page_params.push(("pageSize".to_string(), PAGE_SIZE.to_string()));
+            Box::new(service.get_with_pages::<R>(&path, page_params)
+                .map(move |(r, pages)| (items(r), pages)))
+        };
+
+        let concurrency = self.concurrency;
+        return Box::new(page(1).and_then(move |(first, pages)| -> Fetch<Vec<T>> {
+            match pages {
+                Some(pages) => Box::new(stream::iter_ok(2..=pages)
+                    .map(move |n| page(n).map(|(p, _)| p))
+                    .buffered(concurrency)
+                    .concat2()
+                    .map(move |others| first.into_iter().chain(others).collect())),
+                None => Box::new(future::loop_fn((first, 1), move |(all, n)| -> Fetch<PageLoop<T>> {
+                    if all.len() < n * PAGE_SIZE {
+                        return Box::new(future::ok(future::Loop::Break(all)));
+                    }
+                    Box::new(page(n + 1).map(move |(p, _)| {
+                        future::Loop::Continue((all.into_iter().chain(p).collect(), n + 1))
+                    }))
+                })),
+            }
+        }));
+    }
+
+    fn get<O: DeserializeOwned + Send + 'static>(&self, path: &str, params: Params) -> Fetch<O> {
+        return Box::new(self.get_with_pages(path, params).map(|(body, _)| body));
+    }
+
+    /// Body of the response, with the number of pages when the response is paginated.
+    fn get_with_pages<O: DeserializeOwned + Send + 'static>(&self, path: &str, params: Params) -> Fetch<(O, Option<usize>)> {
+        let request = ApiRequest::new("GET", path).with_query(&params);
+
+        return Box::new(self.transport.send_async(request)
+            .and_then(|response| {
+                let pages = page_count(&response);
+                response.decode().map(|body| (body, pages))
+            }));
+    }
+}
+
+/// Number of pages of a paginated response, from its `X-Pages` header.
+fn page_count(response: &ApiResponse) -> Option<usize> {
+    return response.header("X-Pages")
+        .and_then(|v| v.parse().ok());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_count_from_headers() {
+        let response = |headers: &[(&str, &str)]| ApiResponse {
+            status: 200,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            body: serde_json::Value::Null,
+        };
+
+        assert_eq!(page_count(&response(&[])), None);
+        assert_eq!(page_count(&response(&[("x-pages", "nope")])), None);
+        assert_eq!(page_count(&response(&[("x-pages", "3")])), Some(3));
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Result as IoResult;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+pub const DEFAULT_CACHE_TTL_MINUTES: i64 = 24 * 60;
+
+/// Local copy of Teamwork responses which rarely change (projects, task lists, tasks, account),
+/// kept in ~/.teamwork-cache and reused until they are older than the ttl.
+pub struct Cache {
+    /// File the cache is written to, `None` to keep it in memory only.
+    path: Option<PathBuf>,
+    company_id: String,
+    ttl: Duration,
+    entries: Mutex<CacheEntries>,
+}
+
+type CacheEntries = HashMap<String, CacheEntry>;
+
+/// Content of ~/.teamwork-cache : the entries of each company.
+#[derive(Deserialize, Serialize, Default)]
+struct CacheFile {
+    companies: HashMap<String, CacheEntries>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+struct CacheEntry {
+    fetched_at: DateTime<Utc>,
+    value: serde_json::Value,
+}
+
+impl Cache {
+    /// Cache of ~/.teamwork-cache, kept in memory only when there is no home dir.
+    pub fn load(company_id: &str, ttl_minutes: i64) -> Cache {
+        return match get_cache_file() {
+            Some(path) => Cache::load_from_path(path, company_id, ttl_minutes),
+            None => Cache::in_memory(company_id, ttl_minutes),
+        };
+    }
+
+    /// Cache which is never read from nor written to the disk.
+    pub fn in_memory(company_id: &str, ttl_minutes: i64) -> Cache {
+        return Cache {
+            path: None,
+            company_id: company_id.to_string(),
+            ttl: Duration::minutes(ttl_minutes),
+            entries: Mutex::new(HashMap::new()),
+        };
+    }
+
+    fn load_from_path(path: PathBuf, company_id: &str, ttl_minutes: i64) -> Cache {
+        let entries = read_cache_file(&path).companies
+            .remove(company_id)
+            .unwrap_or_default();
+
+        return Cache {
+            path: Some(path),
+            company_id: company_id.to_string(),
+            ttl: Duration::minutes(ttl_minutes),
+            entries: Mutex::new(entries),
+        };
+    }
+
+    pub fn get<T>(&self, key: &str) -> Option<T> where T: DeserializeOwned {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+
+        if entry.fetched_at + self.ttl < Utc::now() {
+            return None;
+        }
+
+        return serde_json::from_value(entry.value.clone()).ok();
+    }
+
+    /// Caches `value` under `key` and writes the cache.
+    pub fn put<T>(&self, key: &str, value: &T) where T: Serialize {
+        self.insert(key, value);
+        self.save();
+    }
+
+    /// Caches `value` under `key` without writing the cache, to save many values at once with [`Cache::save`].
+    pub fn insert<T>(&self, key: &str, value: &T) where T: Serialize {
+        let value = serde_json::to_value(value)
+            .expect("Could not serialize cached value");
+
+        self.entries.lock().unwrap().insert(key.to_string(), CacheEntry {
+            fetched_at: Utc::now(),
+            value,
+        });
+    }
+
+    pub fn remove(&self, key: &str) {
+        let removed = self.entries.lock().unwrap().remove(key).is_some();
+        if removed {
+            self.save();
+        }
+    }
+
+    /// Writes the entries of this company, keeping the ones of the other companies.
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let mut file = read_cache_file(path);
+        file.companies.insert(self.company_id.clone(), self.entries.lock().unwrap().clone());
+
+        // The cache is only an optimisation : failing to write it must not fail the command.
+        if let Ok(json) = serde_json::to_string(&file) {
+            let _ = fs::write(path, json);
+        }
+    }
+}
+
+/// Removes the cached entries of a company.
+pub fn clear_cache(company_id: &str) -> IoResult<()> {
+    return match get_cache_file() {
+        Some(path) => clear_cache_from_path(&path, company_id),
+        None => Ok(()),
+    };
+}
+
+fn clear_cache_from_path(path: &Path, company_id: &str) -> IoResult<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let mut file = read_cache_file(path);
+    file.companies.remove(company_id);
+    if file.companies.is_empty() {
+        return fs::remove_file(path);
+    }
+
+    let json = serde_json::to_string(&file)
+        .expect("Could not serialize cache");
+
+    return fs::write(path, json);
+}
+
+/// Content of the cache file, empty when it is missing or unreadable.
+fn read_cache_file(path: &Path) -> CacheFile {
+    return fs::read_to_string(path).ok()
+        .and_then(|c| serde_json::from_str::<CacheFile>(&c).ok())
+        .unwrap_or_default();
+}
+
+fn get_cache_file() -> Option<PathBuf> {
+    return dirs::home_dir().map(|home_dir| home_dir.join(".teamwork-cache"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_values_expire_and_belong_to_a_company() {
+        let mut path = std::env::temp_dir();
+        path.push(".teamwork-cli-cache_test_cached_values_expire-0b1f7c52-3d0e-4c1a-9a55-41f5c1f3e6a2.json");
+        let _ = fs::remove_file(&path);
+
+        let cache = Cache::load_from_path(path.clone(), "company", 10);
+        cache.put("projects", &vec!["a".to_string(), "b".to_string()]);
+
+        let reloaded = Cache::load_from_path(path.clone(), "company", 10);
+        assert_eq!(reloaded.get::<Vec<String>>("projects"), Some(vec!["a".to_string(), "b".to_string()]));
+
+        let expired = Cache::load_from_path(path.clone(), "company", -1);
+        assert_eq!(expired.get::<Vec<String>>("projects"), None);
+
+        let other_company = Cache::load_from_path(path.clone(), "other-company", 10);
+        assert_eq!(other_company.get::<Vec<String>>("projects"), None);
+
+        other_company.insert("projects", &vec!["c".to_string()]);
+        other_company.insert("account", &"42".to_string());
+        other_company.save();
+        clear_cache_from_path(&path, "company").unwrap();
+
+        assert_eq!(Cache::load_from_path(path.clone(), "company", 10).get::<Vec<String>>("projects"), None);
+        let kept = Cache::load_from_path(path.clone(), "other-company", 10);
+        assert_eq!(kept.get::<Vec<String>>("projects"), Some(vec!["c".to_string()]));
+        assert_eq!(kept.get::<String>("account"), Some("42".to_string()));
+
+        clear_cache_from_path(&path, "other-company").unwrap();
+        assert!(!path.exists());
+    }
+}
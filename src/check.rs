@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+
+use crate::teamwork_service::MissingDay;
+
+/// Exit code when the missing time is over the threshold.
+pub const EXIT_MISSING_TIME: i32 = 1;
+/// Exit code when the missing time could not be computed.
+pub const EXIT_ERROR: i32 = 2;
+
+/// Missing time since a date, compared to the hours tolerated.
+pub struct TimesheetCheck {
+    pub since: NaiveDate,
+    pub missing_days: Vec<MissingDay>,
+    pub threshold_hours: i32,
+}
+
+impl TimesheetCheck {
+    pub fn missing_hours(&self) -> i32 {
+        return self.missing_days.iter().map(|d| d.hours).sum();
+    }
+
+    pub fn is_ok(&self) -> bool {
+        return self.missing_hours() <= self.threshold_hours;
+    }
+
+    /// One line, short enough for a shell prompt or a status bar.
+    pub fn summary(&self) -> String {
+        let missing_hours = self.missing_hours();
+        if missing_hours == 0 {
+            return format!("✔ timesheet complete since {}", self.since.format("%d-%m"));
+        }
+
+        let sign = match self.is_ok() {
+            true => "~",
+            false => "✘",
+        };
+        let first_day = self.missing_days.first().map(|d| d.date).unwrap_or(self.since);
+        return format!(
+            "{} {}h missing on {} days since {}",
+            sign, missing_hours, self.missing_days.len(), first_day.format("%d-%m"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_against_threshold() {
+        let d = |day: u32| NaiveDate::from_ymd_opt(2019, 7, day).unwrap();
+        let check = |threshold_hours: i32, missing_days: Vec<MissingDay>| TimesheetCheck { since: d(1), missing_days, threshold_hours };
+
+        let complete = check(0, vec![]);
+        assert!(complete.is_ok());
+        assert_eq!(complete.summary(), "✔ timesheet complete since 01-07");
+
+        let missing = vec![MissingDay { date: d(3), hours: 4 }, MissingDay { date: d(4), hours: 8 }];
+        let tolerated = check(12, missing.clone());
+        assert!(tolerated.is_ok());
+        assert_eq!(tolerated.summary(), "~ 12h missing on 2 days since 03-07");
+
+        let over = check(8, missing);
+        assert!(!over.is_ok());
+        assert_eq!(over.summary(), "✘ 12h missing on 2 days since 03-07");
+    }
+}
@@ -1,30 +1,48 @@
-use prettytable::Table;
-use crate::teamwork_service::{ProjectsResponse, TimeEntry, Task};
-use crate::teamwork_config::{TeamWorkConfig, TimeOff};
+use std::cmp::Ordering;
+
+use prettytable::{Cell, Row, Table};
+use chrono::{Duration, NaiveDate};
+
+use teamwork_cli::teamwork_service::{MissingDay, PlannedEntry, ProjectDetails, ProjectTime, ProjectsResponse, SavedDay, TimeEntry, TimeEntryCreatedResponse, Task, TaskDetails, TaskList};
+use teamwork_cli::queue::{QueuedDay, QueuedEntry, SyncOutcome};
+use teamwork_cli::reminder::Reminder;
+use teamwork_cli::timer::format_duration;
+use teamwork_cli::week_grid::WeekGrid;
+use teamwork_cli::team::PersonMissing;
+use teamwork_cli::teamwork_config::{Alias, AliasKind, TeamWorkConfig, TimeEntryTemplate, TimeOff};
 
 pub fn print_projects(project_response: &ProjectsResponse, config: &TeamWorkConfig) {
     let mut table = Table::new();
-    table.add_row(row!["#id", "Alias", "Name"]);
+    table.add_row(row!["#id", "Alias", "Name", "Billable"]);
 
     for p in project_response.projects.iter() {
-        let alias = config.get_alias(&p.id)
+        let alias = config.get_alias(AliasKind::Project, &p.id)
             .map(|a| a.alias.as_str())
             .unwrap_or( "--");
-        table.add_row(row![p.id, alias, p.name]);
+        let billable = match config.billable_projects.contains(&p.id) {
+            true => "💰",
+            false => "",
+        };
+        table.add_row(row![p.id, alias, p.name, billable]);
     }
 
     table.print_tty(true);
 }
 
-pub fn print_time_entries(entries: &Vec<TimeEntry>, _config: &TeamWorkConfig) {
+pub fn print_time_entries(entries: &[TimeEntry], _config: &TeamWorkConfig) {
     let mut table = Table::new();
-    table.add_row(row!["#id", "Date", "Task", "Description", "Hours"]);
+    table.add_row(row!["#id", "Date", "Task", "Description", "Hours", "Billable", "Tags"]);
 
     for e in entries.iter() {
         let date = e.date.format("%d-%m-%Y").to_string();
 
         let task_desc = format!("{}\n> {}\n> {}", e.project_name, e.todo_list_name, e.todo_item_name);
-        table.add_row(row![e.id, date, task_desc, e.description, e.hours()]);
+        let billable = match e.is_billable() {
+            true => "💰",
+            false => "",
+        };
+        let time = format_duration(Duration::minutes(e.minutes().into()));
+        table.add_row(row![e.id, date, task_desc, e.description, time, billable, e.tag_names().join(", ")]);
     }
 
     table.print_tty(true);
@@ -32,10 +50,10 @@ pub fn print_time_entries(entries: &Vec<TimeEntry>, _config: &TeamWorkConfig) {
 
 pub fn print_tasks(tasks: Vec<Task>) {
     let mut table = Table::new();
-    table.add_row(row!["Id", "Name"]);
+    table.add_row(row!["Id", "Project", "Task list", "Name"]);
 
     for t in tasks {
-        table.add_row(row![t.id, t.name]);
+        table.add_row(row![t.id, t.project_name, t.todo_list_name, t.name]);
     }
 
     table.print_tty(true);
@@ -54,3 +72,300 @@ pub fn print_times_off(times_off: Vec<&TimeOff>) {
 
     table.print_tty(true);
 }
+
+pub fn print_aliases(aliases: &[Alias]) {
+    let mut table = Table::new();
+    table.add_row(row!["Alias", "Kind", "#id"]);
+
+    for a in aliases {
+        table.add_row(row![a.alias, a.kind, a.id]);
+    }
+
+    table.print_tty(true);
+}
+
+pub fn print_templates(templates: &[TimeEntryTemplate]) {
+    let mut table = Table::new();
+    table.add_row(row!["Name", "Task", "Duration", "Days", "Description"]);
+
+    for t in templates {
+        let duration = format!("{}h{:02}", t.minutes / 60, t.minutes % 60);
+        let weekdays = match t.weekdays.is_empty() {
+            true => "working days".to_string(),
+            false => t.weekdays.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", "),
+        };
+        let days = match t.every_weeks {
+            1 => weekdays,
+            n => format!("{}\nevery {} weeks", weekdays, n),
+        };
+        table.add_row(row![t.name, t.task, duration, days, t.description]);
+    }
+
+    table.print_tty(true);
+}
+
+pub fn print_queue(queue: &[QueuedEntry]) {
+    let mut table = Table::new();
+    table.add_row(row!["#id", "Date", "Task", "Description", "Time", "Status"]);
+
+    for q in queue {
+        let date = q.date().format("%d-%m-%Y").to_string();
+        let time = format!("{}h{:02}", q.entry.hours, q.entry.minutes.parse::<i32>().unwrap_or(0));
+        let status = match &q.last_error {
+            Some(e) => format!("{} failed attempts\n{}", q.attempts, e),
+            None => "pending".to_string(),
+        };
+        table.add_row(row![q.id, date, q.target(), q.entry.description, time, status]);
+    }
+
+    table.print_tty(true);
+}
+
+pub fn print_time_entry_created(response: &TimeEntryCreatedResponse) {
+    let id = response.id.clone().unwrap_or_else(|| "unknown".to_string());
+    match response.status.as_str() {
+        "OK" => println!("\t ✔️ (#id : {})", id),
+        _ => {
+            println!("\t ❓ {} (#id : {})", response.status, id);
+        }
+    }
+}
+
+/// Days planned by `save_time`, with the hours remaining before each one.
+pub fn print_saved_days(saved_days: &[SavedDay], hours: i32, description: &str, dry_run: bool) {
+    println!("Start adding time entries. Remaining hours : {}", hours);
+
+    let mut remaining_input_hours = hours;
+    for saved in saved_days {
+        print_planned_day(&saved.day, remaining_input_hours, description);
+        match &saved.response {
+            Some(response) => print_time_entry_created(response),
+            None if !dry_run => println!("\t 💤 (no work)"),
+            None => {}
+        }
+
+        remaining_input_hours -= saved.day.hours;
+    }
+}
+
+/// Days planned by `queue_time`, with the hours remaining before each one.
+pub fn print_queued_days(queued_days: &[QueuedDay], hours: i32, description: &str, dry_run: bool) {
+    println!("Start queueing time entries, entries already in Teamwork are not known offline. Remaining hours : {}", hours);
+
+    let mut remaining_input_hours = hours;
+    for queued_day in queued_days {
+        print_planned_day(&queued_day.day, remaining_input_hours, description);
+        match &queued_day.queued {
+            Some(queued) => println!("\t ⏳ (queued #{})", queued.id),
+            None if !dry_run => println!("\t 💤 (no work)"),
+            None => {}
+        }
+
+        remaining_input_hours -= queued_day.day.hours;
+    }
+}
+
+fn print_planned_day(day: &MissingDay, remaining_input_hours: i32, description: &str) {
+    println!("{} - {} / {} : {}",
+             day.date.format("%Y%m%d"),
+             day.hours,
+             remaining_input_hours,
+             description);
+}
+
+pub fn print_filled_days(filled_days: &[SavedDay], description: &str) {
+    for filled in filled_days {
+        println!("{} - {} : {}", filled.day.date.format("%Y%m%d"), filled.day.hours, description);
+        if let Some(response) = &filled.response {
+            print_time_entry_created(response);
+        }
+    }
+}
+
+pub fn print_applied_templates(applied: &[(PlannedEntry, Option<TimeEntryCreatedResponse>)]) {
+    for (entry, response) in applied {
+        println!("{} - {}h{:02} [{}] : {}",
+                 entry.date.format("%Y%m%d"),
+                 entry.minutes / 60,
+                 entry.minutes % 60,
+                 entry.template,
+                 entry.description);
+        if let Some(response) = response {
+            print_time_entry_created(response);
+        }
+    }
+}
+
+pub fn print_sync_outcomes(outcomes: &[(QueuedEntry, SyncOutcome)]) {
+    for (queued, outcome) in outcomes {
+        println!("#{} {} - {}h{:02} on {} : {}",
+                 queued.id,
+                 queued.date().format("%Y%m%d"),
+                 queued.entry.hours,
+                 queued.entry.minutes.parse::<i32>().unwrap_or(0),
+                 queued.target(),
+                 queued.entry.description);
+        match outcome {
+            SyncOutcome::Posted(id) => println!("\t ✔️ (#id : {})", id),
+            SyncOutcome::Kept(error) => println!("\t ❌ {}, kept in queue", error),
+            SyncOutcome::Skipped => {}
+        }
+    }
+}
+
+pub fn print_reminder(reminder: &Reminder, since: NaiveDate) {
+    let message = match &reminder.message {
+        Some(m) => m,
+        None => return println!("Your timesheet is complete since {}", since.format("%d-%m-%Y")),
+    };
+    println!("{}", message);
+
+    if reminder.quiet {
+        println!("Quiet hours, no notification sent");
+    }
+    for (channel, e) in reminder.failures.iter() {
+        println!("Could not notify through {:?} : {}", channel, e);
+    }
+}
+
+pub fn print_week_grid(grid: &WeekGrid) {
+    let mut table = Table::new();
+
+    let mut header = vec![Cell::new("Task")];
+    header.extend((0..7).map(|day| Cell::new(&grid.date(day).format("%a %d").to_string())));
+    header.push(Cell::new("Total"));
+    table.add_row(Row::new(header));
+
+    let hours_row = |label: &str, hours: &[i32; 7]| {
+        let mut cells = vec![Cell::new(label)];
+        cells.extend(hours.iter().map(|h| Cell::new(&h.to_string())));
+        cells.push(Cell::new(&hours.iter().sum::<i32>().to_string()));
+        Row::new(cells)
+    };
+
+    for row in grid.rows.iter() {
+        table.add_row(hours_row(&row.task.name, &row.hours));
+    }
+    table.add_row(hours_row("Already logged", &grid.logged));
+
+    let totals = grid.day_totals();
+    let mut total_cells = vec![Cell::new("Total / expected")];
+    total_cells.extend(totals.iter().zip(grid.expected.iter()).map(|(total, expected)| {
+        let warning = match total.cmp(expected) {
+            Ordering::Less => " ⚠",
+            Ordering::Equal => "",
+            Ordering::Greater => " ⚠⚠",
+        };
+        Cell::new(&format!("{}/{}{}", total, expected, warning))
+    }));
+    total_cells.push(Cell::new(&format!("{}/{}", totals.iter().sum::<i32>(), grid.expected.iter().sum::<i32>())));
+    table.add_row(Row::new(total_cells));
+
+    table.print_tty(true);
+}
+
+pub fn print_tasklists(tasklists: &[TaskList], config: &TeamWorkConfig) {
+    let mut table = Table::new();
+    table.add_row(row!["#id", "Alias", "Name", "Uncompleted tasks"]);
+
+    for t in tasklists {
+        let alias = config.get_alias(AliasKind::Tasklist, &t.id)
+            .map(|a| a.alias.as_str())
+            .unwrap_or("--");
+        table.add_row(row![t.id, alias, t.name, t.uncompleted_count]);
+    }
+
+    table.print_tty(true);
+}
+
+pub fn print_task_details(task: &TaskDetails, logged_minutes: i64) {
+    let mut table = Table::new();
+
+    let assignees = task.assignees.clone()
+        .filter(|a| !a.is_empty())
+        .map(|a| a.split('|').map(|n| n.trim()).collect::<Vec<_>>().join(", "))
+        .unwrap_or_else(|| "--".to_string());
+    let status = match task.completed {
+        true => "completed",
+        false => "open",
+    };
+    let sub_tasks = task.sub_tasks.iter()
+        .map(|t| format!("#{} {}", t.id, t.name))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    table.add_row(row!["#id", task.id]);
+    table.add_row(row!["Task", format!("{}\n> {}\n> {}", task.project_name, task.todo_list_name, task.name)]);
+    table.add_row(row!["Status", status]);
+    table.add_row(row!["Assignees", assignees]);
+    table.add_row(row!["Estimated", format!("{}h{:02}", task.estimated_minutes / 60, task.estimated_minutes % 60)]);
+    table.add_row(row!["Logged", format!("{}h{:02}", logged_minutes / 60, logged_minutes % 60)]);
+    table.add_row(row!["Sub tasks", sub_tasks]);
+    table.add_row(row!["Description", task.description]);
+
+    table.print_tty(true);
+}
+
+pub fn print_project_details(project: &ProjectDetails, tasklists: &[TaskList], time: &ProjectTime, budget_minutes: Option<i64>) {
+    let mut table = Table::new();
+
+    let format_date = |d: &str| NaiveDate::parse_from_str(d, "%Y%m%d")
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| "--".to_string());
+    let company = project.company.as_ref().map(|c| c.name.as_str()).unwrap_or("--");
+    let owner = project.owner.as_ref().map(|o| o.name.as_str()).unwrap_or("--");
+    let uncompleted: u32 = tasklists.iter().map(|t| t.uncompleted_count).sum();
+
+    table.add_row(row!["#id", project.id]);
+    table.add_row(row!["Name", project.name]);
+    table.add_row(row!["Company", company]);
+    table.add_row(row!["Status", project.status]);
+    table.add_row(row!["Dates", format!("{} → {}", format_date(&project.start_date), format_date(&project.end_date))]);
+    table.add_row(row!["Owner", owner]);
+    table.add_row(row!["Task lists", format!("{} ({} uncompleted tasks)", tasklists.len(), uncompleted)]);
+    table.add_row(row!["Logged", format!("{}\n{} billable\n{} non billable", format_minutes(time.total), format_minutes(time.billable), format_minutes(time.non_billable))]);
+    table.add_row(row!["Logged by me", format_minutes(time.mine)]);
+    if let Some(budget) = budget_minutes {
+        let progress = match budget {
+            0 => 0,
+            b => time.total * 100 / b,
+        };
+        table.add_row(row!["Budget", format!("{}\n{} remaining ({}% used)", format_minutes(budget), format_minutes(budget - time.total), progress)]);
+    }
+    table.add_row(row!["Description", project.description]);
+
+    table.print_tty(true);
+}
+
+pub fn print_team_missing(report: &[PersonMissing]) {
+    let mut table = Table::new();
+
+    let weeks = report.iter()
+        .flat_map(|p| p.weeks.keys())
+        .collect::<std::collections::BTreeSet<_>>();
+
+    let mut header = vec![Cell::new("#id"), Cell::new("Name")];
+    header.extend(weeks.iter().map(|w| Cell::new(&format!("Week of\n{}", w.format("%d-%m-%Y")))));
+    header.push(Cell::new("Total"));
+    table.add_row(Row::new(header));
+
+    for p in report {
+        let mut cells = vec![Cell::new(&p.person.id), Cell::new(&p.person.name())];
+        cells.extend(weeks.iter().map(|w| match p.weeks.get(w).unwrap_or(&0) {
+            0 => Cell::new("--"),
+            h => Cell::new(&h.to_string()),
+        }));
+        cells.push(Cell::new(&p.total().to_string()));
+        table.add_row(Row::new(cells));
+    }
+
+    table.print_tty(true);
+}
+
+fn format_minutes(minutes: i64) -> String {
+    let sign = match minutes < 0 {
+        true => "-",
+        false => "",
+    };
+    return format!("{}{}h{:02}", sign, minutes.abs() / 60, minutes.abs() % 60);
+}
@@ -0,0 +1,541 @@
+use std::error::Error;
+use std::time::Duration as StdDuration;
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use ratatui::{DefaultTerminal, Frame};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table};
+
+use teamwork_cli::parsing::{parse_date, parse_time_duration, week_start};
+use teamwork_cli::queue::queued_time_entries;
+use teamwork_cli::teamwork_config::{get_config, save_config, star_task, unstar_task, TeamWorkConfig};
+use teamwork_cli::teamwork_service::{EntryTarget, MissingDay, Task, TeamWorkService, TimeEntry, TimeInput};
+use teamwork_cli::timer::{clear_timer, format_duration, get_timer, save_timer, Timer};
+use teamwork_cli::week_grid::WeekGrid;
+
+const MISSING_DAYS_LOOKBACK: i64 = 30;
+const RECENT_ENTRIES: i32 = 20;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const HELP: &str = "Tab pane · ↑↓ select · l log time · s start/stop timer · p pause/resume · o time off · * star · r refresh · q quit";
+
+/// Panes which can be focused with Tab.
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Starred,
+    Recent,
+    Missing,
+}
+
+/// Action run once all the fields of the form shown at the bottom of the screen are filled.
+enum FormAction {
+    LogTime(Task),
+    StopTimer,
+    TimeOff,
+}
+
+struct Form {
+    action: FormAction,
+    labels: Vec<&'static str>,
+    values: Vec<String>,
+    current: usize,
+}
+
+/// Full screen view of the current week, refreshed from Teamwork with `r`.
+pub struct Dashboard {
+    service: TeamWorkService,
+    config: TeamWorkConfig,
+    week_start: NaiveDate,
+    week_entries: Vec<TimeEntry>,
+    recent_entries: Vec<TimeEntry>,
+    missing_days: Vec<MissingDay>,
+    starred_tasks: Vec<Task>,
+    timer: Option<Timer>,
+    pane: Pane,
+    starred_state: ListState,
+    recent_state: ListState,
+    missing_state: ListState,
+    form: Option<Form>,
+    status: String,
+}
+
+impl Dashboard {
+    pub fn new(config: &TeamWorkConfig) -> Dashboard {
+        let today = Local::now().date_naive();
+
+        return Dashboard {
+            service: TeamWorkService::new(config),
+            config: config.clone(),
+            week_start: week_start(today),
+            week_entries: vec![],
+            recent_entries: vec![],
+            missing_days: vec![],
+            starred_tasks: vec![],
+            timer: None,
+            pane: Pane::Starred,
+            starred_state: ListState::default(),
+            recent_state: ListState::default(),
+            missing_state: ListState::default(),
+            form: None,
+            status: String::new(),
+        };
+    }
+
+    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
+        self.refresh();
+
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+
+        return result;
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Polling with a timeout keeps the running timer ticking.
+            if !event::poll(StdDuration::from_secs(1))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Reloads the config, the timer and the entries of Teamwork.
+    fn refresh(&mut self) {
+        if let Ok(Some(config)) = get_config() {
+            self.config = config;
+        }
+        self.timer = get_timer().unwrap_or_else(|e| {
+            self.status = format!("Could not read timer : {}", e);
+            None
+        });
+
+        match self.fetch() {
+            Ok(skipped) if skipped.is_empty() => self.status = format!("Refreshed at {}", Local::now().format("%H:%M")),
+            Ok(skipped) => self.status = format!("Refreshed at {}, starred tasks {} could not be read",
+                                                 Local::now().format("%H:%M"),
+                                                 skipped.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(", ")),
+            Err(e) => self.status = format!("Could not get time entries : {}", e),
+        }
+
+        clamp_selection(&mut self.starred_state, self.starred_tasks.len());
+        clamp_selection(&mut self.recent_state, self.recent_entries.len());
+        clamp_selection(&mut self.missing_state, self.missing_days.len());
+    }
+
+    /// Returns the ids of the starred tasks which could not be read.
+    fn fetch(&mut self) -> Result<Vec<usize>, Box<dyn Error>> {
+        let today = Local::now().date_naive();
+        let queued = queued_time_entries()?;
+        self.service = self.service.clone().with_queued_entries(queued.clone());
+
+        let mut week_entries = self.service.last_time_entries(500, Some(self.week_start))?;
+        week_entries.extend(queued.iter().filter(|e| e.date.date_naive() >= self.week_start).cloned());
+        self.week_entries = week_entries;
+
+        let mut recent_entries = queued;
+        recent_entries.extend(self.service.last_time_entries(RECENT_ENTRIES, None)?);
+        self.recent_entries = recent_entries;
+
+        self.missing_days = self.service.get_missing_days(
+            today - Duration::days(MISSING_DAYS_LOOKBACK),
+            &self.config.times_off.iter(),
+        )?;
+
+        let starred = self.service.get_tasks(&self.config.starred_tasks)?;
+        self.starred_tasks = starred.tasks;
+
+        return Ok(starred.failures.into_iter().map(|(id, _)| id).collect());
+    }
+
+    fn selected_task(&self) -> Option<Task> {
+        return match self.pane {
+            Pane::Starred => self.starred_state.selected()
+                .and_then(|i| self.starred_tasks.get(i))
+                .cloned(),
+            Pane::Recent => self.recent_state.selected()
+                .and_then(|i| self.recent_entries.get(i))
+                .and_then(|e| e.task()),
+            Pane::Missing => None,
+        };
+    }
+
+    /// The missing day selected, or the first one.
+    fn selected_day(&self) -> Option<&MissingDay> {
+        let index = match self.pane {
+            Pane::Missing => self.missing_state.selected().unwrap_or(0),
+            _ => 0,
+        };
+
+        return self.missing_days.get(index);
+    }
+
+    /// Handles a key press, returns `false` to quit.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        if self.form.is_some() {
+            self.handle_form_key(code);
+            return true;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab => self.pane = match self.pane {
+                Pane::Starred => Pane::Recent,
+                Pane::Recent => Pane::Missing,
+                Pane::Missing => Pane::Starred,
+            },
+            KeyCode::BackTab => self.pane = match self.pane {
+                Pane::Starred => Pane::Missing,
+                Pane::Recent => Pane::Starred,
+                Pane::Missing => Pane::Recent,
+            },
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Char('r') => self.refresh(),
+            KeyCode::Char('l') => match self.selected_task() {
+                Some(task) => self.open_log_time_form(task),
+                None => self.status = "Select a starred or recent task to log time on".to_string(),
+            },
+            KeyCode::Char('s') => self.start_or_stop_timer(),
+            KeyCode::Char('p') => self.pause_or_resume_timer(),
+            KeyCode::Char('o') => {
+                let date = self.selected_day()
+                    .map(|d| d.date)
+                    .unwrap_or_else(|| Local::now().date_naive());
+                self.form = Some(Form {
+                    action: FormAction::TimeOff,
+                    labels: vec!["Date", "Hours"],
+                    values: vec![date.format("%Y-%m-%d").to_string(), "8".to_string()],
+                    current: 0,
+                });
+            }
+            KeyCode::Char('*') => self.toggle_star(),
+            _ => {}
+        }
+
+        return true;
+    }
+
+    fn handle_form_key(&mut self, code: KeyCode) {
+        let form = match self.form.as_mut() {
+            Some(f) => f,
+            None => return,
+        };
+
+        match code {
+            KeyCode::Esc => {
+                self.form = None;
+                self.status = "Cancelled".to_string();
+            }
+            KeyCode::Backspace => {
+                form.values[form.current].pop();
+            }
+            KeyCode::Char(c) => form.values[form.current].push(c),
+            KeyCode::Up | KeyCode::BackTab if form.current > 0 => form.current -= 1,
+            KeyCode::Enter | KeyCode::Down | KeyCode::Tab if form.current + 1 < form.labels.len() => form.current += 1,
+            KeyCode::Enter => {
+                let form = self.form.take().unwrap();
+                self.submit_form(form);
+            }
+            _ => {}
+        }
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let (state, len) = match self.pane {
+            Pane::Starred => (&mut self.starred_state, self.starred_tasks.len()),
+            Pane::Recent => (&mut self.recent_state, self.recent_entries.len()),
+            Pane::Missing => (&mut self.missing_state, self.missing_days.len()),
+        };
+        if len == 0 {
+            return;
+        }
+
+        let current = state.selected().unwrap_or(0) as i32;
+        state.select(Some((current + delta).rem_euclid(len as i32) as usize));
+    }
+
+    fn open_log_time_form(&mut self, task: Task) {
+        let (date, hours) = match self.selected_day() {
+            Some(d) => (d.date, d.hours),
+            None => (Local::now().date_naive(), 8),
+        };
+
+        self.form = Some(Form {
+            action: FormAction::LogTime(task),
+            labels: vec!["Start date", "Hours", "Description"],
+            values: vec![date.format("%Y-%m-%d").to_string(), format!("{}h", hours), String::new()],
+            current: 2,
+        });
+    }
+
+    fn start_or_stop_timer(&mut self) {
+        if self.timer.is_some() {
+            self.form = Some(Form {
+                action: FormAction::StopTimer,
+                labels: vec!["Description"],
+                values: vec![String::new()],
+                current: 0,
+            });
+            return;
+        }
+
+        let task = match self.selected_task() {
+            Some(t) => t,
+            None => return self.status = "Select a starred or recent task to start a timer on".to_string(),
+        };
+
+        let timer = Timer::start(&task.id.to_string(), &task.to_string(), Local::now());
+        match save_timer(&timer) {
+            Ok(()) => {
+                self.status = format!("⏱️ Timer started on {}", timer.task_name);
+                self.timer = Some(timer);
+            }
+            Err(e) => self.status = format!("Could not save timer : {}", e),
+        }
+    }
+
+    fn pause_or_resume_timer(&mut self) {
+        let now = Local::now();
+        let timer = match &self.timer {
+            Some(t) if t.is_running() => t.pause(now),
+            Some(t) => t.resume(now),
+            None => return self.status = "No timer running".to_string(),
+        };
+
+        match save_timer(&timer) {
+            Ok(()) => self.timer = Some(timer),
+            Err(e) => self.status = format!("Could not save timer : {}", e),
+        }
+    }
+
+    fn toggle_star(&mut self) {
+        let task = match self.selected_task() {
+            Some(t) => t,
+            None => return,
+        };
+
+        let result = match self.config.starred_tasks.contains(&task.id) {
+            true => unstar_task(&task.id),
+            false => star_task(task.id),
+        };
+
+        match result {
+            Ok(()) => self.refresh(),
+            Err(e) => self.status = format!("Could not star task : {}", e),
+        }
+    }
+
+    fn submit_form(&mut self, form: Form) {
+        let result = match &form.action {
+            FormAction::LogTime(task) => self.log_time(task, &form.values),
+            FormAction::StopTimer => self.stop_timer(&form.values[0]),
+            FormAction::TimeOff => self.save_time_off(&form.values),
+        };
+
+        match result {
+            Ok(status) => {
+                self.refresh();
+                self.status = status;
+            }
+            Err(FormError::Invalid(field, message)) => {
+                self.status = format!("Oups ! {}", message);
+                self.form = Some(Form { current: field, ..form });
+            }
+            Err(FormError::Failed(message)) => self.status = message,
+        }
+    }
+
+    fn log_time(&self, task: &Task, values: &[String]) -> Result<String, FormError> {
+        let today = Local::now().date_naive();
+        let start_date = parse_date(&values[0], today)
+            .map_err(|e| FormError::Invalid(0, e))?;
+        let hours = parse_hours(&values[1])
+            .map_err(|e| FormError::Invalid(1, e))?;
+
+        let target = EntryTarget::Task(task.id.to_string());
+        let billing = self.service.default_billing(&target)
+            .map_err(|e| FormError::Failed(format!("Could not get task : {}", e)))?;
+        let input = TimeInput { target, start_date, hours, description: values[2].clone(), billing };
+        let saved_days = self.service.save_time(&input, false, &self.config.times_off.iter())
+            .map_err(|e| FormError::Failed(format!("Could not save time : {}", e)))?;
+
+        let saved_hours: i32 = saved_days.iter()
+            .filter(|s| s.response.is_some())
+            .map(|s| s.day.hours)
+            .sum();
+
+        return Ok(format!("✔ {}h logged on {}", saved_hours, task.name));
+    }
+
+    fn stop_timer(&self, description: &str) -> Result<String, FormError> {
+        let timer = match &self.timer {
+            Some(t) => t,
+            None => return Err(FormError::Failed("No timer running".to_string())),
+        };
+
+        let rounding = self.config.timer_rounding.unwrap_or_default();
+        let minutes = rounding.round(timer.elapsed(Local::now()).num_seconds());
+
+        if minutes > 0 {
+            self.service.log_timer(timer, minutes, description)
+                .map_err(|e| FormError::Failed(format!("Could not save time entry, the timer is kept : {}", e)))?;
+        }
+
+        clear_timer()
+            .map_err(|e| FormError::Failed(format!("Could not remove timer : {}", e)))?;
+
+        return Ok(format!("✔ {}h{:02} logged on {}", minutes / 60, minutes % 60, timer.task_name));
+    }
+
+    fn save_time_off(&self, values: &[String]) -> Result<String, FormError> {
+        let date = parse_date(&values[0], Local::now().date_naive())
+            .map_err(|e| FormError::Invalid(0, e))?;
+        let hours = values[1].trim().parse::<i32>()
+            .map_err(|_| FormError::Invalid(1, format!("Could not parse {} as a number of hours", values[1])))?;
+
+        save_config(&self.config.with_time_off(date.format("%Y-%m-%d").to_string(), hours));
+
+        return Ok(format!("✔ {}h off on {}", hours, date.format("%Y-%m-%d")));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, bottom] = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(frame.area());
+        let [top, lists] = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main);
+        let [timesheet_area, side] = Layout::horizontal([Constraint::Min(0), Constraint::Length(36)]).areas(top);
+        let [timer_area, missing_area] = Layout::vertical([Constraint::Length(4), Constraint::Min(0)]).areas(side);
+        let [starred_area, recent_area] = Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(lists);
+
+        frame.render_widget(self.timesheet(), timesheet_area);
+        frame.render_widget(self.timer_paragraph(), timer_area);
+
+        let missing = self.missing_days.iter()
+            .map(|d| ListItem::new(format!("{} {} : {}h", WEEKDAYS[d.date.weekday().num_days_from_monday() as usize], d.date.format("%Y-%m-%d"), d.hours)))
+            .collect::<Vec<_>>();
+        let missing_title = format!("Missing ({}h)", self.missing_days.iter().map(|d| d.hours).sum::<i32>());
+        frame.render_stateful_widget(self.list(missing, &missing_title, Pane::Missing), missing_area, &mut self.missing_state);
+
+        let starred = self.starred_tasks.iter()
+            .map(|t| ListItem::new(t.to_string()))
+            .collect::<Vec<_>>();
+        frame.render_stateful_widget(self.list(starred, "Starred tasks", Pane::Starred), starred_area, &mut self.starred_state);
+
+        let recent = self.recent_entries.iter()
+            .map(|e| ListItem::new(format!("{} {} {} : {}", e.date.format("%Y-%m-%d"), format_duration(Duration::minutes(e.minutes().into())), e.todo_item_name, e.description)))
+            .collect::<Vec<_>>();
+        frame.render_stateful_widget(self.list(recent, "Recent entries", Pane::Recent), recent_area, &mut self.recent_state);
+
+        let bottom_paragraph = match &self.form {
+            Some(form) => {
+                let fields = form.labels.iter().zip(form.values.iter()).enumerate()
+                    .map(|(i, (label, value))| match i == form.current {
+                        true => format!("[{} ? {}█]", label, value),
+                        false => format!("{} ? {}", label, value),
+                    })
+                    .collect::<Vec<_>>();
+                Paragraph::new(format!("{}\n{}", fields.join("  "), "Enter next/submit · Esc cancel"))
+                    .block(Block::bordered().title(form.action.title()))
+            }
+            None => Paragraph::new(format!("{}\n{}", self.status, HELP))
+                .block(Block::bordered()),
+        };
+        frame.render_widget(bottom_paragraph, bottom);
+    }
+
+    fn timesheet(&self) -> Table<'_> {
+        let grid = WeekGrid::new(self.week_start, vec![], &self.week_entries.iter(), &self.config.times_off.iter());
+
+        let mut header = vec!["Task".to_string()];
+        header.extend(WEEKDAYS.iter().map(|d| d.to_string()));
+        header.push("Total".to_string());
+
+        let mut rows = grid.logged_by_task.iter()
+            .map(|(task, hours)| hours_row(task, hours))
+            .collect::<Vec<_>>();
+        rows.push(hours_row("Total", &grid.day_totals()).style(Style::default().add_modifier(Modifier::BOLD)));
+
+        let mut widths = vec![Constraint::Min(20)];
+        widths.extend([Constraint::Length(5); 7]);
+        widths.push(Constraint::Length(6));
+
+        return Table::new(rows, widths)
+            .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(format!("Week of {}", self.week_start.format("%Y-%m-%d"))));
+    }
+
+    fn timer_paragraph(&self) -> Paragraph<'_> {
+        let text = match &self.timer {
+            Some(t) => format!("{}\n{} ({})",
+                               t.task_name,
+                               format_duration(t.elapsed(Local::now())),
+                               match t.is_running() {
+                                   true => "running",
+                                   false => "paused",
+                               }),
+            None => "No timer running".to_string(),
+        };
+
+        return Paragraph::new(text).block(Block::bordered().title("Timer"));
+    }
+
+    fn list<'b>(&self, items: Vec<ListItem<'b>>, title: &str, pane: Pane) -> List<'b> {
+        let mut block = Block::bordered().title(title.to_string());
+        if self.pane == pane {
+            block = block.border_style(Style::default().fg(Color::Yellow));
+        }
+
+        return List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    }
+}
+
+enum FormError {
+    /// The field at this index could not be parsed, the form is shown again.
+    Invalid(usize, String),
+    Failed(String),
+}
+
+impl FormAction {
+    fn title(&self) -> String {
+        return match self {
+            FormAction::LogTime(task) => format!("Log time on {}", task.name),
+            FormAction::StopTimer => "Stop timer".to_string(),
+            FormAction::TimeOff => "Time off".to_string(),
+        };
+    }
+}
+
+fn parse_hours(input: &str) -> Result<i32, String> {
+    return match parse_time_duration(input) {
+        Some(h) if h > 0 => Ok(h),
+        _ => Err(format!("Could not parse {} as a number of hours", input)),
+    };
+}
+
+fn clamp_selection(state: &mut ListState, len: usize) {
+    let selected = match len {
+        0 => None,
+        _ => Some(state.selected().unwrap_or(0).min(len - 1)),
+    };
+    state.select(selected);
+}
+
+fn hours_row(label: &str, hours: &[i32; 7]) -> Row<'static> {
+    let mut cells = vec![label.to_string()];
+    cells.extend(hours.iter().map(|h| match h {
+        0 => "·".to_string(),
+        h => h.to_string(),
+    }));
+    cells.push(hours.iter().sum::<i32>().to_string());
+
+    return Row::new(cells);
+}
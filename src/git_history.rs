@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::process::Command;
+
+use chrono::NaiveDate;
+use regex::Regex;
+
+use crate::teamwork_config::{AliasKind, TeamWorkConfig};
+use crate::teamwork_service::MissingDay;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub date: NaiveDate,
+    pub branch: String,
+    pub subject: String,
+}
+
+/// A time entry built from the commits of a day on a task, to be reviewed before being posted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedEntry {
+    pub date: NaiveDate,
+    /// Resolved task id, `None` when no branch pattern nor alias matched.
+    pub task_id: Option<String>,
+    pub hours: i32,
+    pub description: String,
+}
+
+/// Reads the commits of `author` in all branches of the repository, between `since` and `until` included.
+pub fn read_commits(
+    repository: &str,
+    author: Option<&str>,
+    since: NaiveDate,
+    until: NaiveDate,
+) -> Result<Vec<Commit>, Box<dyn Error>> {
+    let author = match author {
+        Some(a) => a.to_string(),
+        None => git(repository, &["config", "user.email"])?.trim().to_string(),
+    };
+
+    let output = git(repository, &[
+        "log",
+        "--all",
+        "--source",
+        "--no-merges",
+        &format!("--author={}", author),
+        &format!("--since={} 00:00:00", since.format("%Y-%m-%d")),
+        &format!("--until={} 23:59:59", until.format("%Y-%m-%d")),
+        "--date=short",
+        "--pretty=format:%ad%x09%S%x09%s",
+    ])?;
+
+    let commits = output.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let date = NaiveDate::parse_from_str(fields.next()?, "%Y-%m-%d").ok()?;
+            let branch = fields.next()?
+                .trim_start_matches("refs/heads/")
+                .trim_start_matches("refs/remotes/")
+                .to_string();
+            let subject = fields.next()?.to_string();
+
+            Some(Commit { date, branch, subject })
+        })
+        .collect();
+
+    return Ok(commits);
+}
+
+fn git(repository: &str, args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repository)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(format!("git {} failed in {} : {}",
+                           args[0],
+                           repository,
+                           String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+
+    return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+}
+
+/// Finds the task of a branch : the first matching branch pattern, then a task alias found in the branch name.
+pub fn task_of_branch(branch: &str, config: &TeamWorkConfig) -> Option<String> {
+    let git_config = config.git.clone().unwrap_or_default();
+
+    let from_pattern = git_config.branch_patterns.iter()
+        .find(|p| Regex::new(&p.pattern).map(|r| r.is_match(branch)).unwrap_or(false))
+        .and_then(|p| config.resolve_id(AliasKind::Task, &p.task).ok());
+    if from_pattern.is_some() {
+        return from_pattern;
+    }
+
+    return branch
+        .split(['/', '-', '_'])
+        .filter_map(|part| config.aliases.iter().find(|a| a.kind == AliasKind::Task && a.alias == part))
+        .map(|a| a.id.clone())
+        .next();
+}
+
+/// Groups commits by day and task, sharing the missing hours of each day between its tasks by number of commits.
+/// Days without missing time are ignored.
+pub fn propose_entries(commits: &[Commit], missing_days: &[MissingDay], config: &TeamWorkConfig) -> Vec<ProposedEntry> {
+    let mut by_day: BTreeMap<NaiveDate, BTreeMap<Option<String>, Vec<String>>> = BTreeMap::new();
+    for c in commits {
+        let subjects = by_day.entry(c.date).or_default()
+            .entry(task_of_branch(&c.branch, config)).or_default();
+        if !subjects.contains(&c.subject) {
+            subjects.push(c.subject.clone());
+        }
+    }
+
+    let mut proposals = vec![];
+    for day in missing_days {
+        let tasks = match by_day.get(&day.date) {
+            Some(t) => t,
+            None => continue,
+        };
+
+        let total_commits: usize = tasks.values().map(|s| s.len()).sum();
+        let mut remaining_hours = day.hours;
+        let mut day_proposals: Vec<ProposedEntry> = tasks.iter()
+            .map(|(task_id, subjects)| {
+                let hours = day.hours * subjects.len() as i32 / total_commits as i32;
+                remaining_hours -= hours;
+
+                ProposedEntry {
+                    date: day.date,
+                    task_id: task_id.clone(),
+                    hours,
+                    description: subjects.join("; "),
+                }
+            })
+            .collect();
+
+        if let Some(busiest) = day_proposals.iter_mut().max_by_key(|p| p.hours) {
+            busiest.hours += remaining_hours;
+        }
+
+        proposals.extend(day_proposals.into_iter().filter(|p| p.hours > 0));
+    }
+
+    return proposals;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::teamwork_config::{Alias, BranchPattern, GitConfig};
+
+    #[test]
+    fn test_missing_hours_are_shared_by_commits() {
+        let config = TeamWorkConfig {
+            company_id: "test-company-id".to_string(),
+            token: "test-token".to_string(),
+            aliases: vec![
+                Alias { kind: AliasKind::Task, id: "7".to_string(), alias: "support".to_string() },
+            ],
+            git: Some(GitConfig {
+                author: None,
+                repositories: vec![],
+                branch_patterns: vec![
+                    BranchPattern { pattern: "^feature/".to_string(), task: "42".to_string() },
+                ],
+            }),
+            ..Default::default()
+        };
+        let day = NaiveDate::from_ymd_opt(2020, 1, 23).unwrap();
+        let commit = |branch: &str, subject: &str| Commit { date: day, branch: branch.to_string(), subject: subject.to_string() };
+        let commits = [
+            commit("feature/login", "Add login form"),
+            commit("feature/login", "Validate password"),
+            commit("fix/support-mail", "Fix mail"),
+        ];
+
+        let proposals = propose_entries(&commits, &[MissingDay { date: day, hours: 8 }], &config);
+
+        assert_eq!(proposals, vec![
+            ProposedEntry { date: day, task_id: Some("42".to_string()), hours: 6, description: "Add login form; Validate password".to_string() },
+            ProposedEntry { date: day, task_id: Some("7".to_string()), hours: 2, description: "Fix mail".to_string() },
+        ]);
+    }
+}
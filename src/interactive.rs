@@ -1,24 +1,89 @@
-use crate::teamwork_config::{TeamWorkConfig, star_task, get_config, unstar_task, is_starred_task};
-use crate::teamwork_service::{TeamWorkService, Project, TaskList, Task};
+use teamwork_cli::teamwork_config::{star_task, get_config, unstar_task, is_starred_task, TeamWorkConfig};
+use teamwork_cli::task_search::rank_tasks;
+use teamwork_cli::teamwork_service::{EntryTarget, TeamWorkService, Project, TaskList, Task, MissingDay, TimeEntryInput, TimeInput};
+use teamwork_cli::parsing::{parse_date, parse_time_duration, week_start};
+use teamwork_cli::queue::queued_time_entries;
+use teamwork_cli::week_grid::{WeekGrid, parse_row};
+use crate::console_printers::{print_saved_days, print_time_entry_created, print_week_grid};
 use dialoguer::{Select, Input, Confirmation};
-use chrono::NaiveDate;
+use chrono::{Local, NaiveDate};
+use std::fmt;
 
-pub struct InteractiveService<'a> {
-    service: TeamWorkService<'a>,
+pub struct InteractiveService {
+    service: TeamWorkService,
 }
 
-impl<'a> InteractiveService<'a> {
-    pub fn new(config: &TeamWorkConfig) -> InteractiveService {
-        let service = TeamWorkService::new(&config);
-        return InteractiveService {
-            service: service.clone(),
-        };
+/// A prompt of the interactive mode. Steps are stacked so that each prompt can go back to the previous one.
+#[derive(Clone)]
+enum Step {
+    MainMenu,
+    RecentTasks,
+    FindTask,
+    StarredTasks,
+    FillWeek,
+    Projects,
+    TaskLists(Project),
+    Tasks(TaskList),
+    TaskActions(Task),
+    NewTimeEntry(EntryTarget),
+}
+
+/// Where to go once a step is handled.
+enum Navigation {
+    Next(Step),
+    Back,
+    Stay,
+    Home,
+    Quit,
+}
+
+const GO_BACK: &str = "⬅ Go back";
+const LOG_ON_PROJECT: &str = "⏱ Log time on the project";
+
+impl InteractiveService {
+    pub fn new(service: TeamWorkService) -> InteractiveService {
+        return InteractiveService { service };
     }
 
     pub fn handle(&self) {
+        let mut steps = vec![Step::MainMenu];
+
+        while let Some(step) = steps.last().cloned() {
+            match self.handle_step(step) {
+                Navigation::Next(next) => steps.push(next),
+                Navigation::Back => {
+                    steps.pop();
+                }
+                Navigation::Stay => {}
+                Navigation::Home => steps.truncate(1),
+                Navigation::Quit => steps.clear(),
+            }
+        }
+    }
+
+    fn handle_step(&self, step: Step) -> Navigation {
+        return match step {
+            Step::MainMenu => self.handle_main_menu(),
+            Step::RecentTasks => self.handle_recent_tasks(),
+            Step::FindTask => self.handle_find_task(),
+            Step::StarredTasks => self.handle_see_starred_tasks(),
+            Step::FillWeek => self.handle_fill_week(),
+            Step::Projects => self.handle_search_task(),
+            Step::TaskLists(project) => self.handle_selected_project(&project),
+            Step::Tasks(tasklist) => self.handle_selected_tasklist(&tasklist),
+            Step::TaskActions(task) => self.handle_selected_task(&task),
+            Step::NewTimeEntry(target) => self.handle_new_time_entry(&target),
+        };
+    }
+
+    fn handle_main_menu(&self) -> Navigation {
         let commands = &[
+            InteractiveCommand::RecentTasks,
+            InteractiveCommand::FindTask,
             InteractiveCommand::SeeStarredTasks,
             InteractiveCommand::SearchTask,
+            InteractiveCommand::FillWeek,
+            InteractiveCommand::Quit,
         ];
 
         let selected_action = Select::new()
@@ -28,188 +93,500 @@ impl<'a> InteractiveService<'a> {
             .interact()
             .expect("Failed to get action");
 
-        match &commands[selected_action] {
-            InteractiveCommand::SeeStarredTasks => self.handle_see_starred_tasks(),
-            InteractiveCommand::SearchTask => self.handle_search_task(),
+        return match &commands[selected_action] {
+            InteractiveCommand::RecentTasks => Navigation::Next(Step::RecentTasks),
+            InteractiveCommand::FindTask => Navigation::Next(Step::FindTask),
+            InteractiveCommand::SeeStarredTasks => Navigation::Next(Step::StarredTasks),
+            InteractiveCommand::SearchTask => Navigation::Next(Step::Projects),
+            InteractiveCommand::FillWeek => Navigation::Next(Step::FillWeek),
+            InteractiveCommand::Quit => Navigation::Quit,
+        };
+    }
+
+    fn handle_recent_tasks(&self) -> Navigation {
+        let recent_tasks = match self.service.last_used_tasks() {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                println!("Could not get last used tasks \n{:#?}", e);
+                return Navigation::Back;
+            }
+        };
+
+        if recent_tasks.is_empty() {
+            println!("No time entry yet");
+            return Navigation::Back;
         }
+
+        return match select_or_back("Choose a task ?", &recent_tasks, true) {
+            Some(i) => Navigation::Next(Step::TaskActions(recent_tasks[i].clone())),
+            None => Navigation::Back,
+        };
     }
 
-    fn handle_see_starred_tasks(&self) {
-        let config = get_config()
-            .expect("Could not get config")
-            .expect("No config yet");
+    fn handle_find_task(&self) -> Navigation {
+        let config = match load_config() {
+            Some(c) => c,
+            None => return Navigation::Back,
+        };
 
-        let starred_tasks: Vec<Task> = config.starred_tasks.iter()
-            .map(|task_id| self.service.get_task(&task_id)
-                .expect(format!("Could not get task #{}", task_id).as_str())
-            )
-            .collect();
+        println!("Loading tasks ...");
+        let tasks = match self.service.task_index() {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                println!("Could not list tasks \n{:#?}", e);
+                return Navigation::Back;
+            }
+        };
+        let recent_tasks: Vec<usize> = self.service.last_used_tasks()
+            .map(|tasks| tasks.iter().map(|t| t.id).collect())
+            .unwrap_or_default();
 
-        let select_task = Select::new()
-            .with_prompt("Choose a task ?")
-            .items(starred_tasks.as_slice())
-            .default(0)
-            .interact()
-            .expect("Failed to get action");
+        loop {
+            let query = Input::<String>::new().with_prompt("Search ?")
+                .interact()
+                .expect("Failed to get search");
+
+            let found: Vec<&Task> = rank_tasks(&tasks, &query, &config.starred_tasks, &recent_tasks)
+                .into_iter()
+                .take(20)
+                .collect();
+            if found.is_empty() {
+                println!("No task matching {}", query);
+                continue;
+            }
 
-        let task = starred_tasks.get(select_task)
-            .expect("Could not get selected selected task");
+            let mut items: Vec<String> = found.iter().map(|t| t.to_string()).collect();
+            items.push("Search again".to_string());
 
-        self.handle_selected_task(&task);
+            match select_or_back("Choose a task ?", &items, true) {
+                Some(i) if i < found.len() => return Navigation::Next(Step::TaskActions(found[i].clone())),
+                Some(_) => continue,
+                None => return Navigation::Back,
+            }
+        }
     }
 
-    fn handle_search_task(&self) {
-        let seach_opt: Option<String> = None;
-        let projects = self.service.list_project(&seach_opt)
-            .expect("Could not list projects")
-            .projects;
-
-        let selected_project = Select::new()
-            .with_prompt("Choose a project ?")
-            .paged(true)
-            .items(projects.as_slice())
-            .default(0)
-            .interact()
-            .expect("Failed to get selected project");
+    fn handle_see_starred_tasks(&self) -> Navigation {
+        let config = match load_config() {
+            Some(c) => c,
+            None => return Navigation::Back,
+        };
+
+        if config.starred_tasks.is_empty() {
+            println!("No starred task yet");
+            return Navigation::Back;
+        }
 
-        let project = projects.get(selected_project)
-            .expect("Could not get selected project");
+        let starred_tasks = self.starred_tasks(&config.starred_tasks);
 
-        self.handle_selected_project(&project);
+        return match select_or_back("Choose a task ?", &starred_tasks, false) {
+            Some(i) => Navigation::Next(Step::TaskActions(starred_tasks[i].clone())),
+            None => Navigation::Back,
+        };
+    }
+
+    /// Starred tasks which can still be read, the deleted or inaccessible ones being reported and skipped.
+    fn starred_tasks(&self, task_ids: &[usize]) -> Vec<Task> {
+        return match self.service.get_tasks(task_ids) {
+            Ok(fetched) => {
+                for (id, e) in fetched.failures.iter() {
+                    println!("Starred task #{} skipped, could not get it \n{:#?}", id, e);
+                }
+                fetched.tasks
+            }
+            Err(e) => {
+                println!("Could not get starred tasks \n{:#?}", e);
+                vec![]
+            }
+        };
     }
 
-    fn handle_selected_project(&self, project: &Project) {
-        let tasklists_list = self.service.list_tasklists(project)
-            .expect(format!("Could not list tasklists of project {}", project.name).as_str());
+    fn handle_fill_week(&self) -> Navigation {
+        let config = match load_config() {
+            Some(c) => c,
+            None => return Navigation::Back,
+        };
+        let today = Local::now().date_naive();
+
+        let date = prompt_parsed(
+            "Week of ? (2019-06-24, today, 1w, 2026-W42)",
+            Some(week_start(today).format("%Y-%m-%d").to_string()),
+            |s| parse_date(s, today),
+        );
+        let monday = week_start(date);
+
+        let mut tasks = self.starred_tasks(&config.starred_tasks);
+        match self.service.last_used_tasks() {
+            Ok(recent_tasks) => tasks.extend(recent_tasks),
+            Err(e) => println!("Could not get last used tasks \n{:#?}", e),
+        }
+        let mut seen = vec![];
+        tasks.retain(|t| match seen.contains(&t.id) {
+            true => false,
+            false => {
+                seen.push(t.id);
+                true
+            }
+        });
 
-        let select_tasklist = Select::new()
-            .with_prompt("Choose a task list ?")
-            .paged(true)
-            .items(tasklists_list.as_slice())
-            .default(0)
+        if tasks.is_empty() {
+            println!("No starred nor recent task yet");
+            return Navigation::Back;
+        }
+
+        let mut time_entries = match self.service.last_time_entries(500, Some(monday)) {
+            Ok(tes) => tes,
+            Err(e) => {
+                println!("Could not get time entries \n{:#?}", e);
+                return Navigation::Back;
+            }
+        };
+        time_entries.extend(match queued_time_entries() {
+            Ok(queued) => queued,
+            Err(e) => {
+                println!("Could not read queue ~/.teamwork-queue : {}", e);
+                return Navigation::Back;
+            }
+        });
+
+        let mut grid = WeekGrid::new(monday, tasks, &time_entries.iter(), &config.times_off.iter());
+
+        loop {
+            print_week_grid(&grid);
+
+            let mut choices: Vec<String> = grid.rows.iter()
+                .map(|r| format!("Edit {}", r.task.name))
+                .collect();
+            choices.push("Submit".to_string());
+
+            let row = match select_or_back("What do you want to do ?", &choices, true) {
+                Some(i) if i < grid.rows.len() => &mut grid.rows[i],
+                Some(_) => break,
+                None => return Navigation::Back,
+            };
+
+            let current = row.hours.iter().map(|h| h.to_string()).collect::<Vec<_>>().join(" ");
+            row.hours = prompt_parsed(
+                &format!("Hours on {} from Monday to Sunday ? (8 8 4h 0 -)", row.task.name),
+                Some(current),
+                parse_row,
+            );
+        }
+
+        let entries = grid.entries();
+        if entries.is_empty() {
+            println!("Nothing to save");
+            return Navigation::Stay;
+        }
+
+        let mut descriptions = vec![];
+        for row in grid.rows.iter().filter(|r| r.hours.iter().any(|h| *h > 0)) {
+            let description = Input::<String>::new()
+                .with_prompt(&format!("Description on {} ?", row.task.name))
+                .interact()
+                .expect("Failed to get input");
+            descriptions.push((row.task.id, description));
+        }
+
+        for (task, date, hours) in entries.iter() {
+            println!("{} - {}h : {}", date.format("%Y-%m-%d"), hours, task.name);
+        }
+        let confirm = Confirmation::new()
+            .with_text(&format!("Save these {} time entries ?", entries.len()))
             .interact()
-            .expect("Failed to get action");
+            .unwrap();
+        if !confirm {
+            return Navigation::Stay;
+        }
+
+        let account = match self.service.get_account() {
+            Ok(a) => a,
+            Err(e) => {
+                println!("Could not get account \n{:#?}", e);
+                return Navigation::Stay;
+            }
+        };
+
+        for (task, date, hours) in entries {
+            let description = descriptions.iter()
+                .find(|(id, _)| *id == task.id)
+                .map(|(_, d)| d.as_str())
+                .unwrap_or("");
+            let time_entry = TimeEntryInput::new(date, hours, description, &account.id);
+
+            match self.service.post_entry(&EntryTarget::Task(task.id.to_string()), &time_entry) {
+                Ok(response) => print_time_entry_created(&response),
+                Err(e) => {
+                    println!("Could not save time, the next entries were not saved \n{:#?}", e);
+                    return Navigation::Home;
+                }
+            }
+        }
+
+        return Navigation::Home;
+    }
+
+    fn handle_search_task(&self) -> Navigation {
+        let seach_opt: Option<String> = None;
+        let projects = match self.service.list_project(&seach_opt) {
+            Ok(response) => response.projects,
+            Err(e) => {
+                println!("Could not list projects \n{:#?}", e);
+                return Navigation::Back;
+            }
+        };
 
-        let tasklist = tasklists_list.get(select_tasklist)
-            .expect("Could not get selected selected tasklist");
+        return match select_or_back("Choose a project ?", &projects, true) {
+            Some(i) => Navigation::Next(Step::TaskLists(projects[i].clone())),
+            None => Navigation::Back,
+        };
+    }
+
+    fn handle_selected_project(&self, project: &Project) -> Navigation {
+        let tasklists_list = match self.service.list_tasklists(&project.id) {
+            Ok(tasklists) => tasklists,
+            Err(e) => {
+                println!("Could not list tasklists of project {} \n{:#?}", project.name, e);
+                return Navigation::Back;
+            }
+        };
 
-        self.handle_selected_tasklist(&tasklist)
+        let mut choices = vec![LOG_ON_PROJECT.to_string()];
+        choices.extend(tasklists_list.iter().map(|t| t.to_string()));
+
+        return match select_or_back("Choose a task list ?", &choices, true) {
+            Some(0) => Navigation::Next(Step::NewTimeEntry(EntryTarget::Project(project.id.clone()))),
+            Some(i) => Navigation::Next(Step::Tasks(tasklists_list[i - 1].clone())),
+            None => Navigation::Back,
+        };
     }
 
-    fn handle_selected_tasklist(&self, tasklist: &TaskList) {
-        let task_list_response = self.service.list_task(tasklist);
+    fn handle_selected_tasklist(&self, tasklist: &TaskList) -> Navigation {
+        let task_list_response = self.service.list_task(&tasklist.id);
         let task_list = match task_list_response {
             Ok(r) => r,
-            Err(err) => panic!("Could not list tasks of tasklist : {}", err)
+            Err(err) => {
+                println!("Could not list tasks of tasklist \n{:#?}", err);
+                return Navigation::Back;
+            }
         };
-        //.expect(format!("Could not list tasks of tasklist {}", tasklist.name).as_str());
 
         let tasks = flatten_tasks(task_list);
 
-        let select_task = Select::new()
-            .with_prompt("Choose a task ?")
-            .paged(true)
-            .items(tasks.as_slice())
-            .default(0)
-            .interact()
-            .expect("Failed to get action");
-
-        let task = tasks.get(select_task)
-            .expect("Could not get selected selected task");
-
-        self.handle_selected_task(&task.task);
+        return match select_or_back("Choose a task ?", &tasks, true) {
+            Some(i) => Navigation::Next(Step::TaskActions(tasks[i].task.clone())),
+            None => Navigation::Back,
+        };
     }
 
-    fn handle_selected_task(&self, task: &Task) {
+    fn handle_selected_task(&self, task: &Task) -> Navigation {
         let star_command = match is_starred_task(&task.id) {
             Ok(is_starred) => match is_starred {
-                true => Commands::UnstarTask(&task),
-                false => Commands::StarTask(&task),
+                true => Commands::UnstarTask(task),
+                false => Commands::StarTask(task),
             },
-            Err(err) => panic!("Could not know if task {} is starred : {}", task.id, err)
+            Err(err) => {
+                println!("Could not know if task {} is starred : {}", task.id, err);
+                return Navigation::Back;
+            }
         };
 
         let actions = &[
-            Commands::EnterTimeEntry(&task),
+            Commands::EnterTimeEntry(task),
             star_command,
+            Commands::Back,
         ];
 
         let select_task = Select::new()
-            .with_prompt("What do you want to do ?")
+            .with_prompt(&format!("What do you want to do on {} ?", task))
             .items(actions)
             .default(0)
             .interact()
             .expect("Failed to get action");
 
-        match actions[select_task] {
-            Commands::Back => println!("Not implemented yet !"),
+        return match actions[select_task] {
+            Commands::Back => Navigation::Back,
             Commands::StarTask(t) => {
                 match star_task(t.id) {
                     Ok(()) => println!("Task was starred !"),
                     Err(err) => println!("Could not star task {}", err),
                 }
+                Navigation::Stay
             }
             Commands::UnstarTask(t) => {
                 match unstar_task(&t.id) {
                     Ok(()) => println!("Task was unstarred !"),
                     Err(err) => println!("Could not unstar task {}", err),
                 }
+                Navigation::Stay
             }
-            Commands::EnterTimeEntry(t) => self.handle_new_time_entry(&t)
-        }
+            Commands::EnterTimeEntry(t) => Navigation::Next(Step::NewTimeEntry(EntryTarget::Task(t.id.to_string()))),
+        };
     }
 
-    fn handle_new_time_entry(&self, task: &Task) {
-        let config = get_config().unwrap().unwrap();
+    fn handle_new_time_entry(&self, target: &EntryTarget) -> Navigation {
+        let config = match load_config() {
+            Some(c) => c,
+            None => return Navigation::Back,
+        };
+        let today = Local::now().date_naive();
 
         let default_date = self.service.last_time_entries(1, None)
             .map(|tes| tes.first()
-                .map(|te| te.date.date().naive_local()))
+                .map(|te| te.date.date_naive()))
             .unwrap_or_else(|_err| None)
-            .map(|date| date.succ())
-            .map(|date| date.format("%Y-%m-%d").to_string());
+            .map(|date| date.succ_opt().unwrap())
+            .unwrap_or(today);
 
-        let mut start_date_input = Input::<String>::new();
-        start_date_input.with_prompt("Start date ?");
-        if let Some(date) = default_date {
-            start_date_input.default(date);
-        }
-        let start_date_str = start_date_input.interact()
-            .unwrap();
-        let start_date = NaiveDate::parse_from_str(start_date_str.as_str(), "%Y-%m-%d")
-            .expect("Could not parse date");
+        let mut start_date = prompt_start_date(default_date, today);
+        let mut hours = prompt_hours(None);
+        let mut description = prompt_description(None);
 
-        let hours_str = Input::<String>::new().with_prompt("Hours ?")
-            .interact()
-            .unwrap();
-        let hours = hours_str.parse::<i32>().unwrap();
+        loop {
+            match self.service.plan_time_entries(start_date, hours, &config.times_off.iter()) {
+                Ok(planned_days) => print_plan(&planned_days, hours),
+                Err(e) => println!("Could not compute the time entries \n{:#?}", e),
+            }
 
-        let description = Input::<String>::new().with_prompt("Description ?")
-            .interact()
-            .unwrap();
+            let actions = [
+                FormAction::Save,
+                FormAction::EditStartDate,
+                FormAction::EditHours,
+                FormAction::EditDescription,
+            ];
+            let action = match select_or_back("What do you want to do ?", &actions, false) {
+                Some(i) => &actions[i],
+                None => return Navigation::Back,
+            };
+
+            match action {
+                FormAction::Save => {
+                    let confirm = Confirmation::new().with_text("Are you sure ?")
+                        .interact()
+                        .unwrap();
+                    if !confirm {
+                        continue;
+                    }
+
+                    let result = self.service.default_billing(target)
+                        .and_then(|billing| {
+                            let input = TimeInput { target: target.clone(), start_date, hours, description: description.clone(), billing };
+                            self.service.save_time(&input, false, &config.times_off.iter())
+                        });
+
+                    match result {
+                        Ok(saved_days) => {
+                            print_saved_days(&saved_days, hours, &description, false);
+                            return Navigation::Home;
+                        }
+                        Err(e) => println!("Could not save time \n{:#?}", e),
+                    }
+                }
+                FormAction::EditStartDate => start_date = prompt_start_date(start_date, today),
+                FormAction::EditHours => hours = prompt_hours(Some(hours)),
+                FormAction::EditDescription => description = prompt_description(Some(description)),
+            }
+        }
+    }
+}
 
-        let dry_run = Confirmation::new().with_text("Dry run ?")
-            .interact()
-            .unwrap();
+/// Prompts until the input is accepted by `parse`, printing why it was not.
+/// The config, or `None` once the reason it could not be read is printed.
+fn load_config() -> Option<TeamWorkConfig> {
+    return match get_config() {
+        Ok(Some(config)) => Some(config),
+        Ok(None) => {
+            println!("No config file ~/.teamwork found. Init it by authenticating with command `auth`");
+            None
+        }
+        Err(e) => {
+            println!("Could not read config ~/.teamwork \n{:#?}", e);
+            None
+        }
+    };
+}
 
-        let mut confirm = true;
-        if !dry_run {
-            confirm = Confirmation::new().with_text("Are you sure ?")
-                .interact()
-                .unwrap();
+fn prompt_parsed<T, F>(prompt: &str, default: Option<String>, parse: F) -> T where F: Fn(&str) -> Result<T, String> {
+    loop {
+        let mut input = Input::<String>::new();
+        input.with_prompt(prompt);
+        if let Some(d) = &default {
+            input.default(d.clone());
         }
+        let value = input.interact()
+            .expect("Failed to get input");
+
+        match parse(&value) {
+            Ok(v) => return v,
+            Err(e) => println!("Oups ! {}", e),
+        }
+    }
+}
+
+fn prompt_start_date(default: NaiveDate, today: NaiveDate) -> NaiveDate {
+    return prompt_parsed(
+        "Start date ? (2019-06-24, yesterday, monday, -3d)",
+        Some(default.format("%Y-%m-%d").to_string()),
+        |s| parse_date(s, today),
+    );
+}
+
+fn prompt_hours(default: Option<i32>) -> i32 {
+    return prompt_parsed(
+        "Hours ? (4, 4h, 2d, 1d4h)",
+        default.map(|h| format!("{}h", h)),
+        |s| match parse_time_duration(s) {
+            Some(h) if h > 0 => Ok(h),
+            _ => Err(format!("Could not parse {} as a number of hours", s)),
+        },
+    );
+}
+
+fn prompt_description(default: Option<String>) -> String {
+    let mut input = Input::<String>::new();
+    input.with_prompt("Description ?");
+    if let Some(d) = default {
+        input.default(d);
+    }
+
+    return input.interact()
+        .expect("Failed to get input");
+}
 
-        if confirm {
-            self.service.save_time(
-                task.id.to_string(),
-                start_date,
-                hours,
-                description,
-                dry_run,
-                &config.times_off.iter(),
-            ).expect("Could not save time");
+
+fn print_plan(planned_days: &[MissingDay], hours: i32) {
+    println!("Time entries to be saved :");
+    for day in planned_days {
+        if day.hours == 0 {
+            println!("\t{} : 💤 (no work)", day.date.format("%Y-%m-%d"));
+        } else {
+            println!("\t{} : {}h", day.date.format("%Y-%m-%d"), day.hours);
         }
     }
+
+    let planned_hours: i32 = planned_days.iter().map(|d| d.hours).sum();
+    if planned_hours < hours {
+        println!("\t⚠ Only {} of the {} hours could be planned", planned_hours, hours);
+    }
+}
+
+/// Selects an item, a last "Go back" item returning `None`.
+fn select_or_back<T>(prompt: &str, items: &[T], paged: bool) -> Option<usize> where T: ToString {
+    let mut choices: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+    choices.push(GO_BACK.to_string());
+
+    let selected = Select::new()
+        .with_prompt(prompt)
+        .paged(paged)
+        .items(choices.as_slice())
+        .default(0)
+        .interact()
+        .expect("Failed to get selection");
+
+    return match selected < items.len() {
+        true => Some(selected),
+        false => None,
+    };
 }
 
 fn flatten_tasks(task_list: Vec<Task>) -> Vec<TaskItem> {
@@ -226,21 +603,22 @@ fn flatten_tasks(task_list: Vec<Task>) -> Vec<TaskItem> {
 }
 
 enum Commands<'a> {
-    // TODO Dealing with back command, it needs to deal with call stack
     Back,
     StarTask(&'a Task),
     UnstarTask(&'a Task),
     EnterTimeEntry(&'a Task),
 }
 
-impl<'a> ToString for Commands<'a> {
-    fn to_string(&self) -> String {
-        return match self {
-            Commands::Back => "Go Back".to_string(),
-            Commands::StarTask(_t) => "Star the task".to_string(),
-            Commands::UnstarTask(_t) => "Unstar the task".to_string(),
-            Commands::EnterTimeEntry(_t) => "Enter a time entry".to_string(),
+impl<'a> fmt::Display for Commands<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match self {
+            Commands::Back => GO_BACK,
+            Commands::StarTask(_t) => "Star the task",
+            Commands::UnstarTask(_t) => "Unstar the task",
+            Commands::EnterTimeEntry(_t) => "Enter a time entry",
         };
+
+        return write!(f, "{}", str);
     }
 }
 
@@ -250,49 +628,56 @@ struct TaskItem {
     is_sub: bool,
 }
 
-impl ToString for TaskItem {
-    fn to_string(&self) -> String {
+impl fmt::Display for TaskItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let t = &self.task;
         return match self.is_sub {
-            true => format!("\t {}", t.name),
-            false => format!("{} ({} sub tasks)", t.name, t.sub_tasks.len())
+            true => write!(f, "\t {}", t.name),
+            false => write!(f, "{} ({} sub tasks)", t.name, t.sub_tasks.len())
         };
     }
 }
 
-impl ToString for Task {
-    fn to_string(&self) -> String {
-        return match self.parent_task.clone() {
-            Some(p) => format!("{} > {} > {} > {}", self.project_name, self.todo_list_name, p.name, self.name),
-            None => format!("{} > {} > {}", self.project_name, self.todo_list_name, self.name),
-        };
-    }
+enum FormAction {
+    Save,
+    EditStartDate,
+    EditHours,
+    EditDescription,
 }
 
-impl ToString for Project {
-    fn to_string(&self) -> String {
-        return self.name.clone();
-    }
-}
+impl fmt::Display for FormAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match self {
+            FormAction::Save => "Save",
+            FormAction::EditStartDate => "Edit start date",
+            FormAction::EditHours => "Edit hours",
+            FormAction::EditDescription => "Edit description",
+        };
 
-impl ToString for TaskList {
-    fn to_string(&self) -> String {
-        return format!("{} ({} tasks)", self.name, self.uncompleted_count);
+        return write!(f, "{}", str);
     }
 }
 
 enum InteractiveCommand {
+    RecentTasks,
+    FindTask,
     SeeStarredTasks,
     SearchTask,
+    FillWeek,
+    Quit,
 }
 
-impl ToString for InteractiveCommand {
-    fn to_string(&self) -> String {
+impl fmt::Display for InteractiveCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let str = match self {
+            InteractiveCommand::RecentTasks => "Recent tasks",
+            InteractiveCommand::FindTask => "Find a task",
             InteractiveCommand::SeeStarredTasks => "See starred tasks",
             InteractiveCommand::SearchTask => "Search tasks",
+            InteractiveCommand::FillWeek => "Fill a week",
+            InteractiveCommand::Quit => "Quit",
         };
 
-        return str.to_string();
+        return write!(f, "{}", str);
     }
 }
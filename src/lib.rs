@@ -0,0 +1,47 @@
+//! Client and time tracking logic of the Teamwork command line.
+//!
+//! The `teamwork-cli` binary is a thin layer on top of this crate, which can be reused by other tools :
+//!
+//! ```no_run
+//! use teamwork_cli::teamwork_config::get_config;
+//! use teamwork_cli::teamwork_service::TeamWorkService;
+//!
+//! let config = get_config().unwrap().expect("No config file ~/.teamwork found");
+//! let service = TeamWorkService::new(&config);
+//!
+//! let since = chrono::NaiveDate::from_ymd_opt(2019, 7, 1).unwrap();
+//! let missing = service.get_missing_entries(since, &config.times_off.iter()).unwrap();
+//! println!("{} hours missing", missing);
+//! ```
+#![allow(clippy::needless_return)]
+
+/// Configuration saved in ~/.teamwork : credentials, aliases, times off, templates...
+pub mod teamwork_config;
+/// Client of the Teamwork API, its models and the workload computation.
+pub mod teamwork_service;
+/// Asynchronous client, for the requests which fan out.
+pub mod async_service;
+/// How requests reach Teamwork : over HTTP, or replayed from recorded exchanges.
+pub mod transport;
+/// On disk cache of the Teamwork responses which rarely change.
+pub mod cache;
+/// Time entries queued while offline, posted later with `sync`.
+pub mod queue;
+/// Timer running on a task.
+pub mod timer;
+/// Parsing of durations and dates, relative or absolute.
+pub mod parsing;
+/// Week of time entries, edited as a grid of hours.
+pub mod week_grid;
+/// Time entries proposed from the git history.
+pub mod git_history;
+/// Fuzzy search of tasks.
+pub mod task_search;
+/// Missing time of every person of a team.
+pub mod team;
+/// Notifications of the missing time.
+pub mod reminder;
+/// Missing time compared to a threshold, for scripts.
+pub mod check;
+/// Logs of the requests and decisions, enabled with `-v` or `TEAMWORK_LOG`.
+pub mod logging;
@@ -0,0 +1,64 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+
+use env_logger::{Builder, Target};
+use log::LevelFilter;
+
+/// Filters of the logs, with the syntax of `RUST_LOG` : `debug`, `teamwork_cli=trace`...
+pub const LOG_ENV: &str = "TEAMWORK_LOG";
+
+/// Level of the logs of this crate for a number of `-v`. Dependencies only log their warnings.
+pub fn verbosity_level(verbosity: u8) -> LevelFilter {
+    return match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+}
+
+/// Logs to stderr, or at the end of `log_file`. `TEAMWORK_LOG`, when set, takes over the verbosity.
+pub fn init(verbosity: u8, log_file: Option<&Path>) -> io::Result<()> {
+    let mut builder = Builder::new();
+    builder.filter_level(LevelFilter::Warn)
+        .filter_module("teamwork_cli", verbosity_level(verbosity))
+        .format_timestamp_millis();
+
+    if let Ok(filters) = env::var(LOG_ENV) {
+        builder.parse_filters(&filters);
+    }
+    if let Some(path) = log_file {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        builder.target(Target::Pipe(Box::new(file)));
+    }
+
+    // Only fails when a logger is already set, which is then kept
+    let _ = builder.try_init();
+
+    return Ok(());
+}
+
+/// `text` with the secret masked, to log requests and configs without leaking the token.
+pub fn redact(text: &str, secret: &str) -> String {
+    if secret.is_empty() {
+        return text.to_string();
+    }
+
+    return text.replace(secret, "***");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_and_verbosity() {
+        assert_eq!(redact(r#"{"token":"abc123","company":"abc"}"#, "abc123"), r#"{"token":"***","company":"abc"}"#);
+        assert_eq!(redact("nothing to hide", ""), "nothing to hide");
+
+        assert_eq!(verbosity_level(0), LevelFilter::Warn);
+        assert_eq!(verbosity_level(1), LevelFilter::Debug);
+        assert_eq!(verbosity_level(3), LevelFilter::Trace);
+    }
+}
@@ -1,23 +1,55 @@
+#![allow(clippy::needless_return)]
+
 #[macro_use]
 extern crate prettytable;
 extern crate reqwest;
 
 use std::error::Error;
+use std::path::PathBuf;
 
-use chrono::{Datelike, NaiveDate, Utc};
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use dialoguer::{Input, Select};
 use structopt::StructOpt;
 
-use teamwork_config::{get_config, save_token_and_company};
+use teamwork_cli::git_history::{propose_entries, read_commits, ProposedEntry};
+use teamwork_cli::cache::clear_cache;
+use teamwork_cli::parsing::{parse_date, parse_minutes_duration, parse_time_duration, week_start};
+use teamwork_cli::team::{self, PersonMissing};
+use teamwork_cli::logging;
+use teamwork_cli::check::{EXIT_ERROR, EXIT_MISSING_TIME, TimesheetCheck};
+use teamwork_cli::reminder::{next_check, remind};
+use teamwork_cli::queue::{enqueue, get_queue, queue_time, queued_time_entries, remove_from_queue, sync};
+use teamwork_cli::teamwork_config::{get_config, save_token_and_company, AliasKind, BranchPattern, remove_alias, rename_alias, RoundingRule, save_alias, save_config, TeamWorkConfig, TimeEntryTemplate, TimeOff, TimerRounding, WorkSchedule, NotificationChannel, QuietHours, SmtpConfig};
+use teamwork_cli::teamwork_service::{Billing, EntryTarget, MissingDay, sum_project_time, Task, TaskInput, TeamWorkService, TimeEntryInput, TimeInput};
+use teamwork_cli::week_grid::parse_row;
+use teamwork_cli::timer::{clear_timer, format_duration, get_timer, save_timer, Timer};
 
-use crate::console_printers::{print_projects, print_tasks, print_time_entries, print_times_off};
+use crate::console_printers::{print_aliases, print_applied_templates, print_filled_days, print_project_details, print_projects, print_queue, print_queued_days, print_reminder, print_saved_days, print_sync_outcomes, print_task_details, print_tasklists, print_tasks, print_team_missing, print_templates, print_time_entries, print_time_entry_created, print_times_off};
 use crate::interactive::InteractiveService;
-use crate::teamwork_config::{save_alias, save_config, TeamWorkConfig, TimeOff};
-use crate::teamwork_service::TeamWorkService;
+use crate::dashboard::Dashboard;
 
 mod interactive;
-mod teamwork_config;
-mod teamwork_service;
 mod console_printers;
+mod dashboard;
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+struct Opt {
+    /// Ignore the local cache of projects, task lists and tasks
+    #[structopt(long = "refresh")]
+    refresh: bool,
+    /// Log the requests and decisions to stderr, -vv to log the bodies too
+    #[structopt(short = "v", long = "verbose", parse(from_occurrences))]
+    verbose: u8,
+    /// Log everything, like -vv
+    #[structopt(long = "debug")]
+    debug: bool,
+    /// Write the logs at the end of this file instead of stderr
+    #[structopt(long = "log-file", parse(from_os_str))]
+    log_file: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Cli,
+}
 
 #[derive(StructOpt, Debug)]
 #[structopt(rename_all = "kebab-case")]
@@ -29,9 +61,136 @@ enum Cli {
         token: String,
     },
     Project(ProjectCommand),
+    Tasklist(TasklistCommand),
+    Task(TaskCommand),
     TimeEntries(TimeEntriesCommand),
     TimeOff(TimeOffCommand),
+    Alias(AliasCommand),
+    Timer(TimerCommand),
+    Templates(TemplatesCommand),
+    Git(GitCommand),
+    /// Post the time entries queued while offline
+    Sync {
+        #[structopt(short = "r", long = "dry-run")]
+        dry_run: bool,
+    },
+    Queue(QueueCommand),
+    Cache(CacheCommand),
     Interactive,
+    /// Full screen dashboard of the current week
+    Tui,
+    Team(TeamCommand),
+    Remind(RemindCommand),
+    /// Exit with 1 when more time than the threshold is missing, 2 when it could not be checked
+    Check {
+        #[structopt(short = "s", default_value = "start of month")]
+        since: String,
+        /// Missing time tolerated, for example 1d or 4h
+        #[structopt(short = "t", default_value = "0")]
+        threshold: String,
+        /// Only set the exit code
+        #[structopt(short = "q", long = "quiet")]
+        quiet: bool,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum RemindCommand {
+    /// Check the missing time at the configured times, or once when run from cron
+    Run {
+        /// Start date of the missing time
+        #[structopt(short = "s", default_value = "start of month")]
+        since: String,
+        #[structopt(long = "once")]
+        once: bool,
+    },
+    /// Times of the day to check the missing time, for example 12:00 17:30
+    At {
+        times: Vec<String>,
+    },
+    /// No notification between start and end, no quiet hours when missing
+    QuietHours {
+        start: Option<String>,
+        end: Option<String>,
+    },
+    /// Notify with a desktop notification
+    Desktop,
+    /// Notify with the terminal bell
+    Bell,
+    /// Notify by posting a JSON message to an url
+    Webhook {
+        url: String,
+    },
+    /// Notify by email
+    Email {
+        #[structopt(short = "s")]
+        server: String,
+        #[structopt(short = "p", default_value = "587")]
+        port: u16,
+        #[structopt(short = "u")]
+        username: Option<String>,
+        #[structopt(short = "w")]
+        password: Option<String>,
+        #[structopt(short = "f")]
+        from: String,
+        #[structopt(short = "t")]
+        to: String,
+    },
+    /// Remove every notification channel
+    ClearChannels,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum TeamCommand {
+    /// Hours missing per week for every person of the company or of a project
+    Missing {
+        /// Start date of the report
+        #[structopt(short = "s")]
+        since: String,
+        /// Project id or alias, the whole company when missing
+        #[structopt(short = "p")]
+        project: Option<String>,
+        /// Print the report as CSV
+        #[structopt(long = "csv")]
+        csv: bool,
+    },
+    /// Set the working hours of a person, from monday to sunday (e.g. "8 8 8 8 4 - -")
+    Schedule {
+        /// Person id
+        person: String,
+        hours: String,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum CacheCommand {
+    Clear,
+    /// Set how long projects, task lists and tasks are cached
+    Ttl {
+        minutes: i64,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum QueueCommand {
+    List {
+        /// Only billable entries
+        #[structopt(long = "billable")]
+        billable: bool,
+        /// Only non billable entries
+        #[structopt(long = "non-billable")]
+        non_billable: bool,
+        /// Only entries with this tag, can be repeated
+        #[structopt(long = "tag")]
+        tags: Vec<String>,
+    },
+    Remove {
+        id: u32,
+    },
 }
 
 #[derive(StructOpt, Debug)]
@@ -47,6 +206,190 @@ enum ProjectCommand {
         #[structopt(short = "n")]
         name: String,
     },
+    Show {
+        /// Project id or alias
+        project: String,
+    },
+    /// Make the time entries of a project billable by default
+    Billable {
+        /// Project id or alias
+        project: String,
+        /// Non billable by default
+        #[structopt(long = "off")]
+        off: bool,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum TasklistCommand {
+    List {
+        /// Project id or alias
+        project: String,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum TaskCommand {
+    List {
+        /// Task list id or alias
+        tasklist: String,
+    },
+    Show {
+        /// Task id or alias
+        task: String,
+    },
+    Create {
+        /// Task list id or alias
+        #[structopt(short = "l")]
+        tasklist: String,
+        #[structopt(short = "n")]
+        name: String,
+        #[structopt(short = "d", default_value = "")]
+        description: String,
+        /// Estimated time, for example 1h30 or 15m
+        #[structopt(short = "e")]
+        estimate: Option<String>,
+        /// Id of the person the task is assigned to
+        #[structopt(short = "a")]
+        assignee: Option<String>,
+    },
+    Complete {
+        /// Task id or alias
+        task: String,
+    },
+    Comment {
+        /// Task id or alias
+        task: String,
+        #[structopt(short = "m")]
+        message: String,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum AliasCommand {
+    List,
+    Add {
+        /// project, tasklist or task
+        #[structopt(short = "k", default_value = "project")]
+        kind: AliasKind,
+        #[structopt(short = "i")]
+        id: String,
+        #[structopt(short = "n")]
+        name: String,
+    },
+    Remove {
+        #[structopt(short = "n")]
+        name: String,
+    },
+    Rename {
+        #[structopt(short = "n")]
+        name: String,
+        #[structopt(short = "r")]
+        new_name: String,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum TimerCommand {
+    Start {
+        /// Task id or alias
+        task: String,
+    },
+    Stop {
+        #[structopt(short = "d", default_value = "")]
+        description: String,
+        #[structopt(short = "r")]
+        dry_run: bool,
+        /// Queue the entry locally, to be posted later with `sync`
+        #[structopt(short = "o", long = "offline")]
+        offline: bool,
+    },
+    Status,
+    Pause,
+    Resume,
+    Discard,
+    /// Set how elapsed time is rounded when the timer is stopped
+    Rounding {
+        /// up, down or nearest
+        #[structopt(short = "r")]
+        rule: RoundingRule,
+        #[structopt(short = "m", default_value = "1")]
+        minutes: i64,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum TemplatesCommand {
+    List,
+    Add {
+        #[structopt(short = "n")]
+        name: String,
+        /// Task id or alias
+        #[structopt(short = "t")]
+        task: String,
+        /// Duration, for example 15m or 1h30
+        #[structopt(short = "m")]
+        duration: String,
+        #[structopt(short = "d")]
+        description: String,
+        /// Comma separated days, for example mon,thu. Every working day when missing
+        #[structopt(short = "w")]
+        weekdays: Option<String>,
+        /// Log the entry one week out of n
+        #[structopt(short = "e", default_value = "1")]
+        every_weeks: u32,
+        /// First day the template applies, counting weeks from there
+        #[structopt(short = "s")]
+        since: Option<String>,
+    },
+    Remove {
+        #[structopt(short = "n")]
+        name: String,
+    },
+    Apply {
+        /// Any day of the week to fill
+        #[structopt(short = "w", long = "week")]
+        week: String,
+        #[structopt(short = "r", long = "dry-run")]
+        dry_run: bool,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum GitCommand {
+    /// Propose time entries from your commits, to review before posting them
+    Propose {
+        #[structopt(short = "s", long = "since")]
+        since: String,
+        #[structopt(short = "u", long = "until")]
+        until: Option<String>,
+        #[structopt(short = "r", long = "dry-run")]
+        dry_run: bool,
+    },
+    AddRepository {
+        path: String,
+    },
+    RemoveRepository {
+        path: String,
+    },
+    /// Author of the commits, the user.email of each repository is used when missing
+    Author {
+        name: Option<String>,
+    },
+    /// Map branches matching a regex to a task
+    Pattern {
+        #[structopt(short = "p")]
+        pattern: String,
+        /// Task id or alias
+        #[structopt(short = "t")]
+        task: String,
+    },
 }
 
 #[derive(StructOpt, Debug)]
@@ -54,7 +397,16 @@ enum ProjectCommand {
 enum TimeEntriesCommand {
     Last {
         #[structopt(short = "n", default_value = "10")]
-        nb: i32
+        nb: i32,
+        /// Only billable entries
+        #[structopt(long = "billable")]
+        billable: bool,
+        /// Only non billable entries
+        #[structopt(long = "non-billable")]
+        non_billable: bool,
+        /// Only entries with this tag, can be repeated
+        #[structopt(long = "tag")]
+        tags: Vec<String>,
     },
     LastTasks,
     Missing {
@@ -65,16 +417,45 @@ enum TimeEntriesCommand {
         included: bool,
     },
     Save {
+        /// Task id or alias
         #[structopt(short = "t")]
-        task_id: String,
+        task_id: Option<String>,
+        /// Project id or alias, to log time on the project itself rather than on a task
+        #[structopt(short = "p")]
+        project: Option<String>,
         #[structopt(short = "s")]
         start_date: String,
         #[structopt(short = "h")]
         hours: String,
         #[structopt(short = "d")]
         description: String,
+        /// Billable, whatever the default of the project
+        #[structopt(long = "billable")]
+        billable: bool,
+        /// Non billable, whatever the default of the project
+        #[structopt(long = "non-billable")]
+        non_billable: bool,
+        /// Tag name, can be repeated
+        #[structopt(long = "tag")]
+        tags: Vec<String>,
         #[structopt(short = "r")]
         dry_run: bool,
+        /// Queue the entries locally, to be posted later with `sync`
+        #[structopt(short = "o", long = "offline")]
+        offline: bool,
+    },
+    Fill {
+        #[structopt(short = "t", long = "task")]
+        task_id: String,
+        #[structopt(short = "s", long = "since")]
+        since: String,
+        #[structopt(short = "d", long = "description")]
+        description: String,
+        #[structopt(short = "r", long = "dry-run")]
+        dry_run: bool,
+        /// Ask which task to use for each week
+        #[structopt(short = "w", long = "per-week")]
+        per_week: bool,
     },
 }
 
@@ -96,19 +477,51 @@ enum TimeOffCommand {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let args = Cli::from_args();
+    let opt = Opt::from_args();
 
-    //println!("{:?}", args);
+    let verbosity = match opt.debug {
+        true => 2,
+        false => opt.verbose,
+    };
+    if let Err(e) = logging::init(verbosity, opt.log_file.as_deref()) {
+        println!("Could not open the log file \n{:#?}", e);
+    }
 
-    match args {
+    let refresh = opt.refresh;
+    match opt.command {
         Cli::Auth { company_id, token } => {
             save_token_and_company(&company_id, &token);
             println!("Company and token saved in ~/.teamwork")
         }
-        _ => {
+        Cli::Check { since, threshold, quiet } => {
+            let exit_code = match check(&since, &threshold, refresh) {
+                Ok(check) => {
+                    if !quiet {
+                        println!("{}", check.summary());
+                    }
+                    match check.is_ok() {
+                        true => 0,
+                        false => EXIT_MISSING_TIME,
+                    }
+                }
+                Err(e) => {
+                    if !quiet {
+                        println!("? could not check the timesheet : {}", e);
+                    }
+                    EXIT_ERROR
+                }
+            };
+            std::process::exit(exit_code);
+        }
+        command => {
             match get_config() {
                 Ok(config) => match config {
-                    Some(c) => handle_command_with_config(&c),
+                    Some(c) => {
+                        if refresh {
+                            clear_cache(&c.company_id)?;
+                        }
+                        handle_command_with_config(command, &c)
+                    }
                     None => println!("No config file ~/.teamwork found. Init it by authenticating with command `auth`"),
                 }
                 Err(e) => println!("Oups ! {}", e),
@@ -119,23 +532,61 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn handle_command_with_config(config: &TeamWorkConfig) {
-    let args = Cli::from_args();
-    match args {
-        Cli::Project(project_cmd) => handle_project_command(project_cmd, &config),
-        Cli::TimeEntries(time_entries_command) => handle_time_entries_command(time_entries_command, &config),
-        Cli::TimeOff(time_off_command) => handle_time_off_command(time_off_command, &config),
-        Cli::Interactive => {
-            let interactive = InteractiveService::new(config);
-            interactive.handle();
+fn handle_command_with_config(command: Cli, config: &TeamWorkConfig) {
+    match command {
+        Cli::Project(project_cmd) => handle_project_command(project_cmd, config),
+        Cli::Tasklist(tasklist_command) => handle_tasklist_command(tasklist_command, config),
+        Cli::Task(task_command) => handle_task_command(task_command, config),
+        Cli::Team(team_command) => handle_team_command(team_command, config),
+        Cli::Remind(remind_command) => handle_remind_command(remind_command, config),
+        Cli::TimeEntries(time_entries_command) => handle_time_entries_command(time_entries_command, config),
+        Cli::TimeOff(time_off_command) => handle_time_off_command(time_off_command, config),
+        Cli::Alias(alias_command) => handle_alias_command(alias_command, config),
+        Cli::Timer(timer_command) => handle_timer_command(timer_command, config),
+        Cli::Templates(templates_command) => handle_templates_command(templates_command, config),
+        Cli::Git(git_command) => handle_git_command(git_command, config),
+        Cli::Sync { dry_run } => {
+            let service = TeamWorkService::new(config);
+            match sync(&service, dry_run) {
+                Ok(outcomes) if outcomes.is_empty() => println!("Nothing to sync"),
+                Ok(outcomes) => print_sync_outcomes(&outcomes),
+                Err(e) => println!("Could not sync queued time entries : {}", e),
+            }
+        }
+        Cli::Queue(queue_command) => handle_queue_command(queue_command),
+        Cli::Cache(CacheCommand::Clear) => clear_cache(&config.company_id).expect("Unable to write file ~/.teamwork-cache"),
+        Cli::Cache(CacheCommand::Ttl { minutes }) => save_config(&TeamWorkConfig {
+            cache_ttl_minutes: Some(minutes),
+            ..config.clone()
+        }),
+        Cli::Interactive => match service_with_queue(config) {
+            Ok(service) => InteractiveService::new(service).handle(),
+            Err(e) => println!("Could not read queue ~/.teamwork-queue : {}", e),
+        },
+        Cli::Tui => {
+            let mut dashboard = Dashboard::new(config);
+            if let Err(e) = dashboard.run() {
+                println!("Oups ! {}", e);
+            }
         }
         _ => {}
     }
 }
 
+/// Client counting the time entries queued offline as logged.
+fn service_with_queue(config: &TeamWorkConfig) -> Result<TeamWorkService, Box<dyn Error>> {
+    let queued = queued_time_entries()?;
+
+    return Ok(TeamWorkService::new(config).with_queued_entries(queued));
+}
+
 fn handle_time_off_command(time_off_command: TimeOffCommand, config: &TeamWorkConfig) {
     match time_off_command {
         TimeOffCommand::Save { date, hours } => {
+            let date = match parse_date_arg(&date) {
+                Ok(d) => d.format("%Y-%m-%d").to_string(),
+                Err(e) => return println!("Oups ! {}", e),
+            };
             let new_config = config.with_time_off(date, hours);
             save_config(&new_config);
         }
@@ -168,27 +619,679 @@ fn handle_project_command(project_cmd: ProjectCommand, config: &TeamWorkConfig)
             println!("List projects ...");
 
             match service.list_project(&token) {
-                Ok(pl) => print_projects(&pl, &config),
+                Ok(pl) => print_projects(&pl, config),
                 Err(e) => println!("Could not list project \n{:#?}", e)
             }
         }
         ProjectCommand::Alias { id, name } => {
-            if let Err(e) = save_alias(&id, &name) {
+            if let Err(e) = save_alias(AliasKind::Project, &id, &name) {
                 println!("Could not save alias : {}", e);
             }
         }
+        ProjectCommand::Billable { project, off } => {
+            let project_id = match config.resolve_id(AliasKind::Project, &project) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            save_config(&config.with_billable_project(&project_id, !off));
+        }
+        ProjectCommand::Show { project } => {
+            let project_id = match config.resolve_id(AliasKind::Project, &project) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            let details = match service.get_project_details(&project_id) {
+                Ok(d) => d,
+                Err(e) => return println!("Could not get project #{} \n{:#?}", project_id, e),
+            };
+            let tasklists = match service.list_tasklists(&project_id) {
+                Ok(t) => t,
+                Err(e) => return println!("Could not list task lists \n{:#?}", e),
+            };
+            let time = match service.get_account().and_then(|a| service.project_logged_time(&project_id)
+                .map(|logged_time| sum_project_time(&logged_time, &a.id))) {
+                Ok(t) => t,
+                Err(e) => return println!("Could not get time entries of project #{} \n{:#?}", project_id, e),
+            };
+            // Budgets are not available on every plan : the details are shown without it
+            let budget = match service.get_time_budget(&project_id) {
+                Ok(budget) => budget,
+                Err(e) => {
+                    println!("Oups ! budget unavailable : {}", e);
+                    None
+                }
+            };
+
+            print_project_details(&details, &tasklists, &time, budget);
+        }
+    }
+}
+
+fn handle_tasklist_command(tasklist_command: TasklistCommand, config: &TeamWorkConfig) {
+    let service = TeamWorkService::new(config);
+
+    match tasklist_command {
+        TasklistCommand::List { project } => {
+            let project_id = match config.resolve_id(AliasKind::Project, &project) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            match service.list_tasklists(&project_id) {
+                Ok(tasklists) => print_tasklists(&tasklists, config),
+                Err(e) => println!("Could not list task lists \n{:#?}", e),
+            }
+        }
     }
 }
 
-fn handle_time_entries_command(time_entries_command: TimeEntriesCommand, config: &TeamWorkConfig) {
+fn handle_task_command(task_command: TaskCommand, config: &TeamWorkConfig) {
     let service = TeamWorkService::new(config);
 
+    match task_command {
+        TaskCommand::List { tasklist } => {
+            let tasklist_id = match config.resolve_id(AliasKind::Tasklist, &tasklist) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            match service.list_task(&tasklist_id) {
+                Ok(tasks) => {
+                    let mut with_sub_tasks = vec![];
+                    for t in tasks {
+                        let sub_tasks = t.sub_tasks.iter()
+                            .map(|st| Task { name: format!("{} > {}", t.name, st.name), ..st.clone() })
+                            .collect::<Vec<_>>();
+                        with_sub_tasks.push(t);
+                        with_sub_tasks.extend(sub_tasks);
+                    }
+                    print_tasks(with_sub_tasks)
+                }
+                Err(e) => println!("Could not list tasks \n{:#?}", e),
+            }
+        }
+        TaskCommand::Show { task } => {
+            let task_id = match config.resolve_id(AliasKind::Task, &task) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            let details = match service.get_task_details(&task_id) {
+                Ok(d) => d,
+                Err(e) => return println!("Could not get task #{} \n{:#?}", task_id, e),
+            };
+            match service.get_task_logged_minutes(&task_id) {
+                Ok(logged_minutes) => print_task_details(&details, logged_minutes),
+                Err(e) => println!("Could not get time entries of task #{} \n{:#?}", task_id, e),
+            }
+        }
+        TaskCommand::Create { tasklist, name, description, estimate, assignee } => {
+            let tasklist_id = match config.resolve_id(AliasKind::Tasklist, &tasklist) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+            let estimated_minutes = match estimate.map(|e| parse_minutes_duration(&e).ok_or(e)) {
+                Some(Ok(m)) => m,
+                Some(Err(e)) => return println!("Could not parse {}. Expected format xxhyy, for example 1h30 or 15m.", e),
+                None => 0,
+            };
+
+            let task = TaskInput { content: name, description, estimated_minutes, assignee };
+            match service.create_task(&tasklist_id, &task) {
+                Ok(response) if response.status == "OK" => println!("✔️ Task #{} created", response.id.unwrap_or_default()),
+                Ok(response) => println!("Could not create task : {}", response.status),
+                Err(e) => println!("Could not create task \n{:#?}", e),
+            }
+        }
+        TaskCommand::Complete { task } => {
+            let task_id = match config.resolve_id(AliasKind::Task, &task) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            match service.complete_task(&task_id) {
+                Ok(response) if response.status == "OK" => println!("✔️ Task #{} completed", task_id),
+                Ok(response) => println!("Could not complete task : {}", response.status),
+                Err(e) => println!("Could not complete task \n{:#?}", e),
+            }
+        }
+        TaskCommand::Comment { task, message } => {
+            let task_id = match config.resolve_id(AliasKind::Task, &task) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            match service.comment_task(&task_id, &message) {
+                Ok(response) if response.status == "OK" => println!("✔️ Comment #{} added", response.id.unwrap_or_default()),
+                Ok(response) => println!("Could not comment task : {}", response.status),
+                Err(e) => println!("Could not comment task \n{:#?}", e),
+            }
+        }
+    }
+}
+
+fn handle_team_command(team_command: TeamCommand, config: &TeamWorkConfig) {
+    match team_command {
+        TeamCommand::Missing { since, project, csv } => {
+            let since = match parse_date_arg(&since) {
+                Ok(d) => d,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+            let project_id = match project.map(|p| config.resolve_id(AliasKind::Project, &p)).transpose() {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            let service = TeamWorkService::new(config);
+            let account = match service.get_account() {
+                Ok(a) => a,
+                Err(e) => return println!("Could not get your account \n{:#?}", e),
+            };
+            let people = match service.list_people(project_id.as_deref()) {
+                Ok(p) => p,
+                Err(e) => return println!("Could not list people \n{:#?}", e),
+            };
+            let entries = match service.all_time_entries(since) {
+                Ok(e) => e,
+                Err(e) => return println!("Could not get time entries \n{:#?}", e),
+            };
+
+            let today = Local::now().date_naive();
+            let report = people.into_iter()
+                .map(|person| {
+                    let person_entries = entries.iter()
+                        .filter(|e| e.person_id == person.id)
+                        .collect::<Vec<_>>();
+                    // Only your own time off is known
+                    let times_off = match person.id == account.id {
+                        true => config.times_off.iter(),
+                        false => [].iter(),
+                    };
+                    let schedule = team::schedule_of(&person.id, &config.work_schedules);
+                    let weeks = team::missing_by_week(since, today, &person_entries, schedule, &times_off);
+                    PersonMissing { person, weeks }
+                })
+                .collect::<Vec<_>>();
+
+            match csv {
+                true => println!("{}", team::to_csv(&report)),
+                false => print_team_missing(&report),
+            }
+        }
+        TeamCommand::Schedule { person, hours } => {
+            let hours = match parse_row(&hours) {
+                Ok(h) => h,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            save_config(&config.with_work_schedule(WorkSchedule { person_id: person, hours }));
+        }
+    }
+}
+
+/// Missing time since `since`, compared to `threshold`.
+fn check(since: &str, threshold: &str, refresh: bool) -> Result<TimesheetCheck, Box<dyn Error>> {
+    let config = get_config()?
+        .ok_or("no config file ~/.teamwork found. Init it by authenticating with command `auth`")?;
+    if refresh {
+        clear_cache(&config.company_id)?;
+    }
+
+    let since = parse_date_arg(since)?;
+    let threshold_hours = parse_time_duration(threshold)
+        .ok_or_else(|| format!("Could not parse {} as a number of hours", threshold))?;
+
+    let service = service_with_queue(&config)?;
+    let missing_days = service.get_missing_days(since, &config.times_off.iter())?;
+
+    return Ok(TimesheetCheck { since, missing_days, threshold_hours });
+}
+
+fn check_and_remind(config: &TeamWorkConfig, since: &str, now: NaiveDateTime) {
+    let result = parse_date_arg(since).map_err(|e| e.into())
+        .and_then(|d| remind(&service_with_queue(config)?, config, d, now).map(|r| (d, r)));
+
+    match result {
+        Ok((since_date, reminder)) => print_reminder(&reminder, since_date),
+        Err(e) => println!("Could not check missing time \n{:#?}", e),
+    }
+}
+
+fn handle_remind_command(remind_command: RemindCommand, config: &TeamWorkConfig) {
+    let mut reminder = config.reminder.clone().unwrap_or_default();
+    let parse_time = |t: &str| NaiveTime::parse_from_str(t, "%H:%M")
+        .map_err(|_| format!("Could not parse {} as a time of the day, expected HH:MM", t));
+
+    let channel = match remind_command {
+        RemindCommand::Run { since, once } => {
+            if once {
+                check_and_remind(config, &since, Local::now().naive_local());
+                return;
+            }
+
+            loop {
+                let now = Local::now().naive_local();
+                let next = match next_check(now, &reminder) {
+                    Some(n) => n,
+                    None => return println!("No check time outside of the quiet hours. Add one with `remind at <HH:MM>`"),
+                };
+                println!("Next check on {}", next.format("%d-%m-%Y %H:%M"));
+                std::thread::sleep((next - now).to_std().unwrap_or_default());
+
+                // Parsed at each check, for relative dates to follow the current day
+                check_and_remind(config, &since, next);
+            }
+        }
+        RemindCommand::At { times } => {
+            reminder.times = match times.iter().map(|t| parse_time(t)).collect() {
+                Ok(t) => t,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+            None
+        }
+        RemindCommand::QuietHours { start, end } => {
+            reminder.quiet_hours = match (start, end) {
+                (Some(start), Some(end)) => match parse_time(&start).and_then(|s| parse_time(&end).map(|e| (s, e))) {
+                    Ok((start, end)) => Some(QuietHours { start, end }),
+                    Err(e) => return println!("Oups ! {}", e),
+                },
+                (None, None) => None,
+                _ => return println!("Oups ! Quiet hours need both a start and an end"),
+            };
+            None
+        }
+        RemindCommand::Desktop => Some(NotificationChannel::Desktop),
+        RemindCommand::Bell => Some(NotificationChannel::Bell),
+        RemindCommand::Webhook { url } => Some(NotificationChannel::Webhook { url }),
+        RemindCommand::Email { server, port, username, password, from, to } => {
+            Some(NotificationChannel::Email(SmtpConfig { server, port, username, password, from, to }))
+        }
+        RemindCommand::ClearChannels => {
+            reminder.channels.clear();
+            None
+        }
+    };
+
+    if let Some(channel) = channel {
+        reminder.channels.retain(|c| c != &channel);
+        reminder.channels.push(channel);
+    }
+
+    save_config(&TeamWorkConfig {
+        reminder: Some(reminder),
+        ..config.clone()
+    });
+}
+
+fn handle_alias_command(alias_command: AliasCommand, config: &TeamWorkConfig) {
+    match alias_command {
+        AliasCommand::List => print_aliases(&config.aliases),
+        AliasCommand::Add { kind, id, name } => {
+            if let Err(e) = save_alias(kind, &id, &name) {
+                println!("Could not save alias : {}", e);
+            }
+        }
+        AliasCommand::Remove { name } => {
+            if let Err(e) = remove_alias(&name) {
+                println!("Could not remove alias : {}", e);
+            }
+        }
+        AliasCommand::Rename { name, new_name } => {
+            if let Err(e) = rename_alias(&name, &new_name) {
+                println!("Could not rename alias : {}", e);
+            }
+        }
+    }
+}
+
+fn handle_templates_command(templates_command: TemplatesCommand, config: &TeamWorkConfig) {
+    match templates_command {
+        TemplatesCommand::List => print_templates(&config.templates),
+        TemplatesCommand::Add { name, task, duration, description, weekdays, every_weeks, since } => {
+            let minutes = match parse_minutes_duration(&duration) {
+                Some(m) => m,
+                None => return println!("Could not parse {}. Expected format xxhyy, for example 1h30 or 15m.", duration),
+            };
+
+            let weekdays = match weekdays.map(|w| w.split(',').map(|d| d.trim().parse::<Weekday>()).collect()) {
+                Some(Ok(w)) => w,
+                Some(Err(_)) => return println!("Could not parse weekdays. Expected for example mon,thu"),
+                None => vec![],
+            };
+
+            let since = match since.map(|s| parse_date_arg(&s)) {
+                Some(Ok(d)) => Some(d.format("%Y-%m-%d").to_string()),
+                Some(Err(e)) => return println!("Oups ! {}", e),
+                None => None,
+            };
+
+            let template = TimeEntryTemplate { name, task, minutes, description, weekdays, every_weeks, since };
+            save_config(&config.with_template(template));
+        }
+        TemplatesCommand::Remove { name } => save_config(&config.without_template(&name)),
+        TemplatesCommand::Apply { week, dry_run } => {
+            let date = match parse_date_arg(&week) {
+                Ok(d) => d,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+            let week_start = week_start(date);
+
+            let mut templates = vec![];
+            for t in config.templates.iter() {
+                match config.resolve_id(AliasKind::Task, &t.task) {
+                    Ok(task) => templates.push(TimeEntryTemplate { task, ..t.clone() }),
+                    Err(e) => return println!("Oups ! template {} : {}", t.name, e),
+                }
+            }
+
+            println!("Applying templates on week of {} ...", week_start.format("%Y-%m-%d"));
+
+            let service = match service_with_queue(config) {
+                Ok(s) => s,
+                Err(e) => return println!("Could not read queue ~/.teamwork-queue : {}", e),
+            };
+            match service.apply_templates(&templates, week_start, dry_run, &config.times_off.iter()) {
+                Ok(applied) if applied.is_empty() => println!("Nothing to log"),
+                Ok(applied) => print_applied_templates(&applied),
+                Err(e) => println!("Could not apply templates \n{:#?}", e),
+            }
+        }
+    }
+}
+
+fn handle_git_command(git_command: GitCommand, config: &TeamWorkConfig) {
+    let mut git = config.git.clone().unwrap_or_default();
+
+    match git_command {
+        GitCommand::Propose { since, until, dry_run } => {
+            let since_date = match parse_date_arg(&since) {
+                Ok(d) => d,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+            let until_date = match until.map(|u| parse_date_arg(&u)) {
+                Some(Ok(d)) => d,
+                Some(Err(e)) => return println!("Oups ! {}", e),
+                None => Local::now().date_naive(),
+            };
+
+            if git.repositories.is_empty() {
+                return println!("No repository configured. Add one with `git add-repository <path>`");
+            }
+
+            let mut commits = vec![];
+            for repository in git.repositories.iter() {
+                match read_commits(repository, git.author.as_deref(), since_date, until_date) {
+                    Ok(c) => commits.extend(c),
+                    Err(e) => println!("Could not read commits of {} : {}", repository, e),
+                }
+            }
+
+            let service = match service_with_queue(config) {
+                Ok(s) => s,
+                Err(e) => return println!("Could not read queue ~/.teamwork-queue : {}", e),
+            };
+            let missing_days = match service.get_missing_days(since_date, &config.times_off.iter()) {
+                Ok(days) => days,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+            let missing_days: Vec<MissingDay> = missing_days.into_iter()
+                .filter(|d| d.date <= until_date)
+                .collect();
+
+            let proposals = propose_entries(&commits, &missing_days, config);
+            if proposals.is_empty() {
+                return println!("No commit found on days with missing time");
+            }
+
+            let account = match service.get_account() {
+                Ok(a) => a,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+            for proposal in proposals {
+                print_proposal(&proposal);
+                if dry_run {
+                    continue;
+                }
+
+                if let Some(entry) = review_proposal(proposal, config) {
+                    let task_id = entry.task_id.clone().unwrap();
+                    let time_entry = TimeEntryInput::new(entry.date, entry.hours, &entry.description, &account.id);
+                    match service.post_entry(&EntryTarget::Task(task_id), &time_entry) {
+                        Ok(response) => print_time_entry_created(&response),
+                        Err(e) => println!("Could not save time entry \n{:#?}", e),
+                    }
+                }
+            }
+            return;
+        }
+        GitCommand::AddRepository { path } => {
+            let absolute_path = match std::fs::canonicalize(&path) {
+                Ok(p) => p.to_string_lossy().to_string(),
+                Err(e) => return println!("Could not find repository {} : {}", path, e),
+            };
+            git.repositories.retain(|r| r != &absolute_path);
+            git.repositories.push(absolute_path);
+        }
+        GitCommand::RemoveRepository { path } => {
+            let absolute_path = std::fs::canonicalize(&path)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.clone());
+            git.repositories.retain(|r| r != &path && r != &absolute_path);
+        }
+        GitCommand::Author { name } => git.author = name,
+        GitCommand::Pattern { pattern, task } => {
+            if let Err(e) = regex::Regex::new(&pattern) {
+                return println!("Oups ! {} is not a valid regex : {}", pattern, e);
+            }
+            git.branch_patterns.retain(|p| p.pattern != pattern);
+            git.branch_patterns.push(BranchPattern { pattern, task });
+        }
+    }
+
+    save_config(&TeamWorkConfig {
+        git: Some(git),
+        ..config.clone()
+    });
+}
+
+fn print_proposal(proposal: &ProposedEntry) {
+    let task = proposal.task_id.clone().unwrap_or_else(|| "no task".to_string());
+    println!("{} - {} [#{}] : {}", proposal.date.format("%Y%m%d"), proposal.hours, task, proposal.description);
+}
+
+/// Asks whether to post, edit or skip a proposed entry. Returns the entry to post.
+fn review_proposal(proposal: ProposedEntry, config: &TeamWorkConfig) -> Option<ProposedEntry> {
+    let mut entry = proposal;
+
+    loop {
+        let choices = match entry.task_id {
+            Some(_) => vec!["Post", "Edit", "Skip"],
+            None => vec!["Edit", "Skip"],
+        };
+        let selected = Select::new()
+            .with_prompt("What do you want to do ?")
+            .items(&choices)
+            .default(0)
+            .interact()
+            .expect("Failed to get action");
+
+        match choices[selected] {
+            "Post" => return Some(entry),
+            "Skip" => return None,
+            _ => {
+                let task = Input::<String>::new()
+                    .with_prompt("Task ?")
+                    .default(entry.task_id.clone().unwrap_or_default())
+                    .interact()
+                    .expect("Failed to get task");
+                entry.task_id = match config.resolve_id(AliasKind::Task, &task) {
+                    Ok(id) => Some(id),
+                    Err(e) => {
+                        println!("Oups ! {}", e);
+                        None
+                    }
+                };
+                entry.hours = Input::<i32>::new()
+                    .with_prompt("Hours ?")
+                    .default(entry.hours)
+                    .interact()
+                    .expect("Failed to get hours");
+                entry.description = Input::<String>::new()
+                    .with_prompt("Description ?")
+                    .default(entry.description.clone())
+                    .interact()
+                    .expect("Failed to get description");
+
+                print_proposal(&entry);
+            }
+        }
+    }
+}
+
+fn handle_timer_command(timer_command: TimerCommand, config: &TeamWorkConfig) {
+    let now = Local::now();
+    let current_timer = match get_timer() {
+        Ok(t) => t,
+        Err(e) => return println!("Oups ! Could not read timer ~/.teamwork-timer : {}", e),
+    };
+
+    match (timer_command, current_timer) {
+        (TimerCommand::Start { task }, None) => {
+            let task_id = match config.resolve_id(AliasKind::Task, &task) {
+                Ok(id) => id,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            let service = TeamWorkService::new(config);
+            let task = match task_id.parse().map(|id| service.get_task(&id)) {
+                Ok(Ok(t)) => t,
+                Ok(Err(e)) => return println!("Could not get task #{} \n{:#?}", task_id, e),
+                Err(_) => return println!("Oups ! {} is not a task id", task_id),
+            };
+
+            let timer = Timer::start(&task_id, &task.to_string(), now);
+            save_timer(&timer).expect("Unable to write file ~/.teamwork-timer");
+            println!("⏱️ Timer started on {}", timer.task_name);
+        }
+        (TimerCommand::Start { .. }, Some(timer)) => {
+            println!("A timer is already running on {} ({})", timer.task_name, format_duration(timer.elapsed(now)));
+        }
+        (TimerCommand::Rounding { rule, minutes }, _) => {
+            let new_config = TeamWorkConfig {
+                timer_rounding: Some(TimerRounding { rule, minutes }),
+                ..config.clone()
+            };
+            save_config(&new_config);
+        }
+        (_, None) => println!("No timer running"),
+        (TimerCommand::Status, Some(timer)) => {
+            let state = match timer.is_running() {
+                true => "running",
+                false => "paused",
+            };
+            println!("{} - {} since {} ({})",
+                     timer.task_name,
+                     format_duration(timer.elapsed(now)),
+                     timer.started_at.format("%Y-%m-%d %H:%M"),
+                     state);
+        }
+        (TimerCommand::Pause, Some(timer)) => {
+            if !timer.is_running() {
+                return println!("Timer already paused at {}", format_duration(timer.elapsed(now)));
+            }
+            save_timer(&timer.pause(now)).expect("Unable to write file ~/.teamwork-timer");
+            println!("⏸️ Timer paused at {}", format_duration(timer.elapsed(now)));
+        }
+        (TimerCommand::Resume, Some(timer)) => {
+            if timer.is_running() {
+                return println!("Timer already running on {}", timer.task_name);
+            }
+            save_timer(&timer.resume(now)).expect("Unable to write file ~/.teamwork-timer");
+            println!("⏱️ Timer resumed on {}", timer.task_name);
+        }
+        (TimerCommand::Discard, Some(timer)) => {
+            clear_timer().expect("Unable to remove file ~/.teamwork-timer");
+            println!("🗑️ Timer on {} discarded ({})", timer.task_name, format_duration(timer.elapsed(now)));
+        }
+        (TimerCommand::Stop { description, dry_run, offline }, Some(timer)) => {
+            let rounding = config.timer_rounding.unwrap_or_default();
+            let minutes = rounding.round(timer.elapsed(now).num_seconds());
+
+            println!("{} - {}h{:02} : {}", timer.task_name, minutes / 60, minutes % 60, description);
+
+            if minutes == 0 {
+                println!("\t 💤 (nothing to save)");
+            } else if !dry_run && offline {
+                let time_entry = TimeEntryInput::for_duration(timer.started_at.naive_local(), minutes, &description, "");
+                match enqueue(&EntryTarget::Task(timer.task_id.clone()), time_entry) {
+                    Ok(queued) => println!("\t ⏳ (queued #{})", queued.id),
+                    Err(e) => return println!("Could not queue time entry, the timer is kept : {}", e),
+                }
+            } else if !dry_run {
+                let service = TeamWorkService::new(config);
+
+                match service.log_timer(&timer, minutes, &description) {
+                    Ok(response) => print_time_entry_created(&response),
+                    Err(e) => return println!("Could not save time entry, the timer is kept. Use --offline to queue it \n{:#?}", e),
+                }
+            }
+
+            if !dry_run {
+                clear_timer().expect("Unable to remove file ~/.teamwork-timer");
+            }
+        }
+    }
+}
+
+fn handle_queue_command(queue_command: QueueCommand) {
+    match queue_command {
+        QueueCommand::List { billable, non_billable, tags } => {
+            let billable = match billable_flag(billable, non_billable) {
+                Ok(b) => b,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            match get_queue() {
+                Ok(mut queue) => {
+                    queue.retain(|q| q.as_time_entry().matches(billable, &tags));
+                    print_queue(&queue)
+                }
+                Err(e) => println!("Could not read queue : {}", e),
+            }
+        }
+        QueueCommand::Remove { id } => match remove_from_queue(id) {
+            Ok(true) => println!("Queued entry #{} removed", id),
+            Ok(false) => println!("No queued entry #{}", id),
+            Err(e) => println!("Could not remove queued entry : {}", e),
+        },
+    }
+}
+
+fn handle_time_entries_command(time_entries_command: TimeEntriesCommand, config: &TeamWorkConfig) {
+    let queued = match queued_time_entries() {
+        Ok(q) => q,
+        Err(e) => return println!("Could not read queue ~/.teamwork-queue : {}", e),
+    };
+    let service = TeamWorkService::new(config).with_queued_entries(queued.clone());
+
     match time_entries_command {
-        TimeEntriesCommand::Last { nb } => {
+        TimeEntriesCommand::Last { nb, billable, non_billable, tags } => {
+            let billable = match billable_flag(billable, non_billable) {
+                Ok(b) => b,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
             println!("Last time entries ...");
 
             match service.last_time_entries(nb, None) {
-                Ok(pl) => print_time_entries(&pl, &config),
+                Ok(pl) => {
+                    let mut entries = queued;
+                    entries.extend(pl);
+                    entries.retain(|e| e.matches(billable, &tags));
+                    print_time_entries(&entries, config)
+                }
                 Err(e) => println!("Could not get last time entries \n{:#?}", e)
             }
         }
@@ -201,10 +1304,12 @@ fn handle_time_entries_command(time_entries_command: TimeEntriesCommand, config:
             }
         }
         TimeEntriesCommand::Missing { since, included: _included } => {
-            println!("Getting missing entries since {} ...", since);
+            let since_date = match parse_date_arg(&since) {
+                Ok(d) => d,
+                Err(e) => return println!("Oups ! {}", e),
+            };
 
-            let since_date = NaiveDate::parse_from_str(&since, "%Y-%m-%d")
-                .expect(&format!("Could not parse {} using format %Y-%m-%d", &since));
+            println!("Getting missing entries since {} ...", since_date.format("%Y-%m-%d"));
 
             match service.get_missing_entries(since_date, &config.times_off.iter()) {
                 Ok(missing_time) => {
@@ -216,34 +1321,140 @@ fn handle_time_entries_command(time_entries_command: TimeEntriesCommand, config:
                 Err(e) => println!("Could not get last time entries \n{:#?}", e)
             }
         }
-        TimeEntriesCommand::Save { task_id, start_date, hours: time, description, dry_run } => {
-            let date = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
-                .expect(&format!("Could not parse {} using format %Y-%m-%d", &start_date));
+        TimeEntriesCommand::Save { task_id, project, start_date, hours: time, description, billable, non_billable, tags, dry_run, offline } => {
+            let billable = match billable_flag(billable, non_billable) {
+                Ok(b) => b,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            let date = match parse_date_arg(&start_date) {
+                Ok(d) => d,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            let hours = match parse_time_duration(time.as_str()) {
+                Some(h) => h,
+                None => return println!("Oups ! Could not parse {}. Expected format xxdyyh, for example 8d4h for 8 days and 4 hours.", &time),
+            };
+
+            let target = match (task_id, project) {
+                (Some(task), None) => config.resolve_id(AliasKind::Task, &task).map(EntryTarget::Task),
+                (None, Some(project)) => config.resolve_id(AliasKind::Project, &project).map(EntryTarget::Project),
+                _ => return println!("Oups ! Expected either a task (-t) or a project (-p)"),
+            };
+            let target = match target {
+                Ok(t) => t,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            if offline {
+                let billing = match (billable, &target) {
+                    (Some(is_billable), _) => Billing { is_billable, tags },
+                    (None, EntryTarget::Project(project_id)) => Billing { is_billable: config.billable_projects.contains(project_id), tags },
+                    (None, EntryTarget::Task(_)) => {
+                        if !config.billable_projects.is_empty() {
+                            println!("The project of the task is not known offline, entries are non billable unless --billable is given");
+                        }
+                        Billing { is_billable: false, tags }
+                    }
+                };
+                let input = TimeInput { target, start_date: date, hours, description, billing };
+                match queue_time(&input, dry_run, &config.times_off.iter()) {
+                    Ok(queued_days) => print_queued_days(&queued_days, input.hours, &input.description, dry_run),
+                    Err(e) => println!("Could not queue times : {}", e),
+                }
+                return;
+            }
+
+            let billing = match billable {
+                Some(is_billable) => Ok(Billing { is_billable, tags }),
+                None => service.default_billing(&target)
+                    .map(|b| Billing { tags, ..b }),
+            };
+            let result = billing.and_then(|billing| {
+                let input = TimeInput { target, start_date: date, hours, description: description.clone(), billing };
+                service.save_time(&input, dry_run, &config.times_off.iter())
+            });
+            match result {
+                Ok(saved_days) => print_saved_days(&saved_days, hours, &description, dry_run),
+                Err(e) => println!("Fail to save times, use --offline to queue them \n{:#?}", e),
+            }
+        }
+        TimeEntriesCommand::Fill { task_id, since, description, dry_run, per_week } => {
+            let since_date = match parse_date_arg(&since) {
+                Ok(d) => d,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            let missing_days = match service.get_missing_days(since_date, &config.times_off.iter()) {
+                Ok(days) => days,
+                Err(e) => return println!("Oups ! {}", e),
+            };
+
+            if missing_days.is_empty() {
+                println!("Nothing to fill since {}", since);
+                return;
+            }
 
-            let hours = parse_time_duration(time.as_str())
-                .expect(&format!("Could not parse {}. Expected format xxdyyh, for example 8d4h for 8 days and 4 hours.", &time));
+            let mut filled = 0;
+            for week in group_by_week(&missing_days) {
+                let week_hours: i32 = week.iter().map(|d| d.hours).sum();
 
-            service.save_time(task_id, date, hours, description, dry_run, &config.times_off.iter())
-                .expect("Fail to save times");
+                let week_task_id = match per_week {
+                    true => Input::<String>::new()
+                        .with_prompt(&format!("Task for week of {} ({} hours missing) ?", week[0].date.format("%Y-%m-%d"), week_hours))
+                        .default(task_id.clone())
+                        .interact()
+                        .expect("Failed to get task"),
+                    false => task_id.clone(),
+                };
+
+                let week_task_id = match config.resolve_id(AliasKind::Task, &week_task_id) {
+                    Ok(id) => id,
+                    Err(e) => return println!("Oups ! {}", e),
+                };
+
+                match service.fill_missing_days(&week_task_id, &week, &description, dry_run) {
+                    Ok(filled_days) => {
+                        print_filled_days(&filled_days, &description);
+                        filled += filled_days.iter().map(|f| f.day.hours).sum::<i32>();
+                    }
+                    Err(e) => return println!("Oups ! {}", e),
+                }
+            }
+
+            match dry_run {
+                true => println!("{} hours would have been filled", filled),
+                false => println!("{} hours filled", filled),
+            }
         }
     }
 }
 
-fn parse_time_duration(time: &str) -> Option<i32> {
-    let time_regex = regex::Regex::new(r#"(([0-9]+)d)?(([0-9]+)h)?"#).unwrap();
-    let hours = time_regex.captures(time)
-        .and_then(|c| {
-            let x = c.iter().collect::<Vec<_>>();
-            if let &[_, _, None, _, None] = &*x {
-                None
-            } else if let &[_, _, input_days, _, input_hours] = &*x {
-                let d = input_days.map(|m| m.as_str().parse::<i32>().unwrap()).unwrap_or(0);
-                let h = input_hours.map(|m| m.as_str().parse::<i32>().unwrap()).unwrap_or(0);
+/// `Some(true)` for `--billable`, `Some(false)` for `--non-billable`, `None` for the default.
+fn billable_flag(billable: bool, non_billable: bool) -> Result<Option<bool>, String> {
+    return match (billable, non_billable) {
+        (true, true) => Err("--billable and --non-billable can not be used together".to_string()),
+        (true, false) => Ok(Some(true)),
+        (false, true) => Ok(Some(false)),
+        (false, false) => Ok(None),
+    };
+}
 
-                Some(d * 8 + h)
-            } else {
-                None
-            }
-        });
-    return hours;
+/// Parses a date argument, relative expressions like `yesterday` being relative to today.
+fn parse_date_arg(input: &str) -> Result<NaiveDate, String> {
+    return parse_date(input, Local::now().date_naive());
+}
+
+fn group_by_week(missing_days: &[MissingDay]) -> Vec<Vec<MissingDay>> {
+    let mut weeks: Vec<Vec<MissingDay>> = vec![];
+
+    for day in missing_days {
+        match weeks.last_mut() {
+            Some(week) if week[0].date.iso_week() == day.date.iso_week() => week.push(day.clone()),
+            _ => weeks.push(vec![day.clone()]),
+        }
+    }
+
+    return weeks;
 }
@@ -0,0 +1,187 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Monday of the week of `date`.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    return date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+}
+
+/// Parses a duration in working hours, like `8d4h` for 8 days and 4 hours. A plain number is a number of hours.
+pub fn parse_time_duration(time: &str) -> Option<i32> {
+    let time_regex = regex::Regex::new(r#"^(([0-9]+)d)?(([0-9]+)h?)?$"#).unwrap();
+    let hours = time_regex.captures(time.trim())
+        .and_then(|c| {
+            let x = c.iter().collect::<Vec<_>>();
+            if let &[_, _, None, _, None] = &*x {
+                None
+            } else if let &[_, _, input_days, _, input_hours] = &*x {
+                // Too many days or hours for an i32 are not a duration
+                let d = match input_days {
+                    Some(m) => m.as_str().parse::<i32>().ok()?,
+                    None => 0,
+                };
+                let h = match input_hours {
+                    Some(m) => m.as_str().parse::<i32>().ok()?,
+                    None => 0,
+                };
+
+                d.checked_mul(8)?.checked_add(h)
+            } else {
+                None
+            }
+        });
+    return hours;
+}
+
+/// Parses a duration in minutes, like `1h30`, `15m` or `2h`.
+pub fn parse_minutes_duration(time: &str) -> Option<i64> {
+    let time_regex = regex::Regex::new(r#"^(([0-9]+)h)?(([0-9]+)m?)?$"#).unwrap();
+    let captures = time_regex.captures(time.trim())?;
+
+    let hours = match captures.get(2) {
+        Some(m) => Some(m.as_str().parse::<i64>().ok()?),
+        None => None,
+    };
+    let minutes = match captures.get(4) {
+        Some(m) => Some(m.as_str().parse::<i64>().ok()?),
+        None => None,
+    };
+    if hours.is_none() && minutes.is_none() {
+        return None;
+    }
+
+    return hours.unwrap_or(0).checked_mul(60)?.checked_add(minutes.unwrap_or(0));
+}
+
+/// Parses a date, relative expressions being relative to `today` :
+/// - `2019-06-24`, `24/06/2019` or an ISO week like `2019-W26` for its monday
+/// - `today`, `yesterday`
+/// - a weekday like `monday` for the last one up to today, `last monday` for the last one before today
+/// - days or weeks ago like `-3d`, `3d`, `2w`
+/// - `start of week`, `start of month` or `start of year`
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let input = input.trim().to_lowercase();
+
+    let date = match input.as_str() {
+        "today" => Some(today),
+        "yesterday" => Some(today - Duration::days(1)),
+        "start of week" => Some(week_start(today)),
+        "start of month" => today.with_day(1),
+        "start of year" => today.with_ordinal(1),
+        _ => None,
+    };
+    if let Some(d) = date {
+        return Ok(d);
+    }
+
+    if let Some(d) = parse_weekday(&input, today) {
+        return Ok(d);
+    }
+
+    let ago_regex = regex::Regex::new(r#"^-?([0-9]+)([dw])$"#).unwrap();
+    if let Some(c) = ago_regex.captures(&input) {
+        let invalid = || format!("Invalid date {} : too far in the past", input);
+        let nb = c[1].parse::<i64>().map_err(|_| invalid())?;
+        let ago = match &c[2] {
+            "w" => Duration::try_weeks(nb),
+            _ => Duration::try_days(nb),
+        };
+        return ago.and_then(|ago| today.checked_sub_signed(ago))
+            .ok_or_else(invalid);
+    }
+
+    let iso_week_regex = regex::Regex::new(r#"^([0-9]{4})-w([0-9]{1,2})$"#).unwrap();
+    if let Some(c) = iso_week_regex.captures(&input) {
+        let year = c[1].parse::<i32>().map_err(|e| e.to_string())?;
+        let week = c[2].parse::<u32>().map_err(|e| e.to_string())?;
+        return NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+            .ok_or_else(|| format!("{} has no week {}", year, week));
+    }
+
+    return NaiveDate::parse_from_str(&input, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&input, "%d/%m/%Y"))
+        .map_err(|_| format!("Could not parse date {}. Expected a date like 2019-06-24, 24/06/2019, 2019-W26, today, yesterday, monday, last friday, -3d, 2w or start of month.", input));
+}
+
+/// Parses `friday` as the last friday up to `today`, and `last friday` as the last one before `today`.
+fn parse_weekday(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (weekday, before) = match input.strip_prefix("last ") {
+        Some(w) => (w.parse::<Weekday>().ok()?, 1),
+        None => (input.parse::<Weekday>().ok()?, 0),
+    };
+
+    let days_since = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    let weeks_before = match days_since == 0 {
+        true => before,
+        false => 0,
+    };
+
+    return Some(today - Duration::days(days_since as i64) - Duration::weeks(weeks_before));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_duration() {
+        assert_eq!(parse_time_duration("8d4h"), Some(68));
+        assert_eq!(parse_time_duration("2d"), Some(16));
+        assert_eq!(parse_time_duration("4h"), Some(4));
+        assert_eq!(parse_time_duration("6"), Some(6));
+        assert_eq!(parse_time_duration("four"), None);
+        assert_eq!(parse_time_duration(""), None);
+        assert_eq!(parse_time_duration("99999999999h"), None);
+        assert_eq!(parse_time_duration("300000000d"), None);
+    }
+
+    #[test]
+    fn test_parse_minutes_duration() {
+        assert_eq!(parse_minutes_duration("1h30"), Some(90));
+        assert_eq!(parse_minutes_duration("15m"), Some(15));
+        assert_eq!(parse_minutes_duration("2h"), Some(120));
+        assert_eq!(parse_minutes_duration("h"), None);
+        assert_eq!(parse_minutes_duration("99999999999999999999m"), None);
+        assert_eq!(parse_minutes_duration("999999999999999999h"), None);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let today = NaiveDate::from_ymd_opt(2020, 1, 23).unwrap();
+
+        assert_eq!(parse_date("2020-01-02", today), Ok(NaiveDate::from_ymd_opt(2020, 1, 2).unwrap()));
+        assert_eq!(parse_date("Today", today), Ok(today));
+        assert_eq!(parse_date("yesterday", today), Ok(NaiveDate::from_ymd_opt(2020, 1, 22).unwrap()));
+        assert_eq!(parse_date("-3d", today), Ok(NaiveDate::from_ymd_opt(2020, 1, 20).unwrap()));
+        assert!(parse_date("23/01", today).is_err());
+    }
+
+    #[test]
+    fn test_parse_natural_date() {
+        // A thursday
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+
+        assert_eq!(parse_date("monday", today), Ok(date(10, 12)));
+        assert_eq!(parse_date("thursday", today), Ok(today));
+        assert_eq!(parse_date("friday", today), Ok(date(10, 9)));
+        assert_eq!(parse_date("last friday", today), Ok(date(10, 9)));
+        assert_eq!(parse_date("last thursday", today), Ok(date(10, 8)));
+        assert_eq!(parse_date("2w", today), Ok(date(10, 1)));
+        assert_eq!(parse_date("3d", today), Ok(date(10, 12)));
+        assert_eq!(parse_date("start of month", today), Ok(date(10, 1)));
+        assert_eq!(parse_date("start of week", today), Ok(date(10, 12)));
+        assert_eq!(parse_date("2026-W42", today), Ok(date(10, 12)));
+        assert_eq!(parse_date("16/10/2026", today), Ok(date(10, 16)));
+        assert!(parse_date("2026-W54", today).is_err());
+        assert!(parse_date("last week", today).is_err());
+    }
+
+    #[test]
+    fn test_parse_date_too_far_in_the_past() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+
+        assert!(parse_date("99999999999w", today).is_err());
+        assert!(parse_date("-99999999999d", today).is_err());
+        assert!(parse_date("99999999999999999999d", today).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use std::error::Error;
+use std::fs;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::PathBuf;
+use std::slice::Iter;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::teamwork_config::TimeOff;
+use crate::teamwork_service::{plan_time, EntryTarget, MissingDay, Tag, TeamWorkService, TimeEntry, TimeEntryInput, TimeInput};
+
+/// A time entry saved while offline, waiting in ~/.teamwork-queue to be posted by `sync`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct QueuedEntry {
+    pub id: u32,
+    pub task_id: String,
+    /// Set, with an empty `task_id`, for an entry logged on a project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// Entry to post, its `person_id` is set when syncing.
+    pub entry: TimeEntryInput,
+    pub queued_at: DateTime<Local>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl QueuedEntry {
+    pub fn target(&self) -> EntryTarget {
+        return match &self.project_id {
+            Some(project_id) => EntryTarget::Project(project_id.clone()),
+            None => EntryTarget::Task(self.task_id.clone()),
+        };
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        return NaiveDate::parse_from_str(&self.entry.date, "%Y%m%d")
+            .expect("Queued entry date should be formatted as %Y%m%d");
+    }
+
+    /// The queued entry as if it was already in Teamwork, to be counted in the workload.
+    pub fn as_time_entry(&self) -> TimeEntry {
+        let date = Utc.from_utc_datetime(&self.date().and_hms_opt(0, 0, 0).unwrap());
+
+        return TimeEntry {
+            id: format!("queued-{}", self.id),
+            description: self.entry.description.clone(),
+            date,
+            hours: self.entry.hours.clone(),
+            minutes: self.entry.minutes.clone(),
+            project_id: self.project_id.clone().unwrap_or_default(),
+            project_name: "⏳ queued".to_string(),
+            todo_list_id: "".to_string(),
+            todo_list_name: "".to_string(),
+            todo_item_id: self.task_id.clone(),
+            todo_item_name: self.target().to_string(),
+            person_id: self.entry.person_id.clone(),
+            is_billable: match self.entry.is_billable {
+                true => "1".to_string(),
+                false => "0".to_string(),
+            },
+            tags: self.entry.tags.split(',')
+                .filter(|t| !t.is_empty())
+                .map(|t| Tag { name: t.to_string() })
+                .collect(),
+        };
+    }
+}
+
+pub fn get_queue() -> Result<Vec<QueuedEntry>, Box<dyn Error>> {
+    let path = get_queue_file()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let file_content = fs::read_to_string(path)?;
+    let queue: Vec<QueuedEntry> = serde_json::from_str(&file_content)?;
+
+    // Checked once here, for `QueuedEntry::date` not to fail later on a file edited by hand
+    if let Some(q) = queue.iter().find(|q| NaiveDate::parse_from_str(&q.entry.date, "%Y%m%d").is_err()) {
+        return Err(format!("Queued entry #{} has an invalid date {}, expected YYYYMMDD", q.id, q.entry.date).into());
+    }
+
+    return Ok(queue);
+}
+
+pub fn save_queue(queue: &[QueuedEntry]) -> IoResult<()> {
+    let path = get_queue_file()?;
+    if queue.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+
+    let json = serde_json::to_string_pretty(queue)
+        .expect("Could not serialize queue");
+
+    return fs::write(path, json);
+}
+
+pub fn enqueue(target: &EntryTarget, entry: TimeEntryInput) -> Result<QueuedEntry, Box<dyn Error>> {
+    let mut queue = get_queue()?;
+
+    let (task_id, project_id) = match target {
+        EntryTarget::Task(task_id) => (task_id.clone(), None),
+        EntryTarget::Project(project_id) => (String::new(), Some(project_id.clone())),
+    };
+    let queued = QueuedEntry {
+        id: queue.iter().map(|q| q.id).max().unwrap_or(0) + 1,
+        task_id,
+        project_id,
+        entry,
+        queued_at: Local::now(),
+        attempts: 0,
+        last_error: None,
+    };
+    queue.push(queued.clone());
+    save_queue(&queue)?;
+
+    return Ok(queued);
+}
+
+pub fn remove_from_queue(id: u32) -> Result<bool, Box<dyn Error>> {
+    let mut queue = get_queue()?;
+    let size = queue.len();
+    queue.retain(|q| q.id != id);
+    save_queue(&queue)?;
+
+    return Ok(queue.len() != size);
+}
+
+/// Queued entries as time entries, so that offline work is counted as logged.
+pub fn queued_time_entries() -> Result<Vec<TimeEntry>, Box<dyn Error>> {
+    let queue = get_queue()?;
+
+    return Ok(queue.iter().map(|q| q.as_time_entry()).collect());
+}
+
+/// A day planned by `queue_time`, with the entry queued on it.
+#[derive(Debug)]
+pub struct QueuedDay {
+    pub day: MissingDay,
+    /// `None` on a dry run or when nothing was left to log on that day.
+    pub queued: Option<QueuedEntry>,
+}
+
+/// Offline counterpart of `TeamWorkService::save_time` : only queued entries and times off are known.
+pub fn queue_time(input: &TimeInput, dry_run: bool, times_off: &Iter<TimeOff>) -> Result<Vec<QueuedDay>, Box<dyn Error>> {
+    let time_entries = queued_time_entries()?;
+    let planned_days = plan_time(input.start_date, input.hours, &time_entries.iter(), times_off);
+
+    let mut queued_days = vec![];
+    for day in planned_days {
+        let queued = match dry_run || day.hours == 0 {
+            true => None,
+            false => {
+                let new_time_entry = TimeEntryInput::new(day.date, day.hours, &input.description, "")
+                    .with_billing(&input.billing);
+                Some(enqueue(&input.target, new_time_entry)?)
+            }
+        };
+
+        queued_days.push(QueuedDay { day, queued });
+    }
+
+    return Ok(queued_days);
+}
+
+/// What `sync` did with a queued entry.
+#[derive(Debug, PartialEq)]
+pub enum SyncOutcome {
+    /// Posted, with the id given by Teamwork.
+    Posted(String),
+    /// Rejected, with the reason. The entry is kept in queue for a later retry.
+    Kept(String),
+    /// Not posted : dry run.
+    Skipped,
+}
+
+/// Posts the queued entries, keeping the ones that failed for a later retry.
+pub fn sync(service: &TeamWorkService, dry_run: bool) -> Result<Vec<(QueuedEntry, SyncOutcome)>, Box<dyn Error>> {
+    let queue = get_queue()?;
+    if queue.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let account = service.get_account()?;
+
+    let mut outcomes = vec![];
+    let mut remaining = vec![];
+    for queued in queue {
+        if dry_run {
+            remaining.push(queued.clone());
+            outcomes.push((queued, SyncOutcome::Skipped));
+            continue;
+        }
+
+        let entry = TimeEntryInput {
+            person_id: account.id.clone(),
+            ..queued.entry.clone()
+        };
+        let error = match service.save_entry(&queued.target(), &entry) {
+            Ok(response) if response.status == "OK" => {
+                let id = response.id.unwrap_or_else(|| "unknown".to_string());
+                outcomes.push((queued, SyncOutcome::Posted(id)));
+                continue;
+            }
+            Ok(response) => response.status,
+            Err(e) => e.to_string(),
+        };
+
+        let kept = QueuedEntry { attempts: queued.attempts + 1, last_error: Some(error.clone()), ..queued };
+        remaining.push(kept.clone());
+        outcomes.push((kept, SyncOutcome::Kept(error)));
+    }
+
+    save_queue(&remaining)?;
+
+    return Ok(outcomes);
+}
+
+fn get_queue_file() -> IoResult<PathBuf> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| IoError::new(ErrorKind::NotFound, "Could not get your home dir"))?;
+
+    return Ok(home_dir.join(".teamwork-queue"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queued_entry_is_counted_on_its_day() {
+        let date = NaiveDate::from_ymd_opt(2020, 1, 23).unwrap();
+        let queued = QueuedEntry {
+            id: 3,
+            task_id: "42".to_string(),
+            project_id: None,
+            entry: TimeEntryInput::new(date, 6, "Development", ""),
+            queued_at: Local::now(),
+            attempts: 0,
+            last_error: None,
+        };
+
+        let time_entry = queued.as_time_entry();
+
+        assert_eq!(time_entry.date.date_naive(), date);
+        assert_eq!(time_entry.minutes(), 6 * 60);
+        assert_eq!(time_entry.todo_item_id, "42");
+    }
+
+    #[test]
+    fn test_project_entries_are_not_counted_as_tasks() {
+        let date = NaiveDate::from_ymd_opt(2020, 1, 23).unwrap();
+        let queued = QueuedEntry {
+            id: 4,
+            task_id: String::new(),
+            project_id: Some("7".to_string()),
+            entry: TimeEntryInput::new(date, 2, "Meeting", ""),
+            queued_at: Local::now(),
+            attempts: 0,
+            last_error: None,
+        };
+
+        assert_eq!(queued.target(), EntryTarget::Project("7".to_string()));
+
+        let time_entry = queued.as_time_entry();
+        assert_eq!(time_entry.project_id, "7");
+        assert_eq!(time_entry.task(), None);
+
+        let legacy: QueuedEntry = serde_json::from_str(r#"{
+            "id": 1, "task_id": "42", "queued_at": "2020-01-23T10:00:00+01:00", "attempts": 0, "last_error": null,
+            "entry": { "description": "", "person-id": "", "date": "20200123", "time": "08:00", "hours": "8", "minutes": "0" }
+        }"#).unwrap();
+        assert_eq!(legacy.target(), EntryTarget::Task("42".to_string()));
+    }
+}
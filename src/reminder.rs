@@ -0,0 +1,139 @@
+use std::error::Error;
+use std::io::Write;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use lettre::{Message, SmtpTransport, Transport};
+use lettre::transport::smtp::authentication::Credentials;
+use notify_rust::Notification;
+use serde::Serialize;
+
+use crate::teamwork_config::{NotificationChannel, ReminderConfig, SmtpConfig, TeamWorkConfig};
+use crate::teamwork_service::TeamWorkService;
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+    missing_hours: i32,
+}
+
+/// Next configured check strictly after `now`, skipping the times in the quiet hours.
+pub fn next_check(now: NaiveDateTime, reminder: &ReminderConfig) -> Option<NaiveDateTime> {
+    let mut times = reminder.times.iter()
+        .filter(|t| !reminder.quiet_hours.as_ref().map(|q| q.contains(**t)).unwrap_or(false))
+        .collect::<Vec<_>>();
+    times.sort();
+
+    let today = now.date();
+    let next = times.iter()
+        .map(|t| today.and_time(**t))
+        .find(|d| d > &now)
+        .or_else(|| times.first().map(|t| (today + Duration::days(1)).and_time(**t)));
+
+    return next;
+}
+
+/// Result of a check by `remind`.
+#[derive(Debug)]
+pub struct Reminder {
+    pub missing_hours: i32,
+    /// Text of the notification, `None` when the timesheet is complete.
+    pub message: Option<String>,
+    /// No notification was sent because the check happened in the quiet hours.
+    pub quiet: bool,
+    /// Channels which could not be notified, with the reason.
+    pub failures: Vec<(NotificationChannel, String)>,
+}
+
+/// Checks the time missing since `since` and notifies it on every channel,
+/// unless `now` is in the quiet hours.
+pub fn remind(service: &TeamWorkService, config: &TeamWorkConfig, since: NaiveDate, now: NaiveDateTime) -> Result<Reminder, Box<dyn Error>> {
+    let reminder = config.reminder.clone().unwrap_or_default();
+
+    let missing_hours = service.get_missing_entries(since, &config.times_off.iter())?;
+    if missing_hours == 0 {
+        return Ok(Reminder { missing_hours, message: None, quiet: false, failures: vec![] });
+    }
+
+    let message = format!("{} hours are missing on your timesheet since {}", missing_hours, since.format("%d-%m-%Y"));
+
+    if reminder.quiet_hours.as_ref().map(|q| q.contains(now.time())).unwrap_or(false) {
+        return Ok(Reminder { missing_hours, message: Some(message), quiet: true, failures: vec![] });
+    }
+
+    let mut failures = vec![];
+    for channel in reminder.channels.iter() {
+        if let Err(e) = notify(channel, &message, missing_hours) {
+            failures.push((channel.clone(), e.to_string()));
+        }
+    }
+
+    return Ok(Reminder { missing_hours, message: Some(message), quiet: false, failures });
+}
+
+fn notify(channel: &NotificationChannel, message: &str, missing_hours: i32) -> Result<(), Box<dyn Error>> {
+    match channel {
+        NotificationChannel::Desktop => {
+            Notification::new()
+                .summary("Teamwork")
+                .body(message)
+                .show()?;
+        }
+        NotificationChannel::Bell => {
+            print!("\x07");
+            std::io::stdout().flush()?;
+        }
+        NotificationChannel::Email(smtp) => send_email(smtp, message)?,
+        NotificationChannel::Webhook { url } => {
+            reqwest::Client::new()
+                .post(url.as_str())
+                .json(&WebhookPayload { text: message, missing_hours })
+                .send()?
+                .error_for_status()?;
+        }
+    }
+
+    return Ok(());
+}
+
+fn send_email(smtp: &SmtpConfig, message: &str) -> Result<(), Box<dyn Error>> {
+    let email = Message::builder()
+        .from(smtp.from.parse()?)
+        .to(smtp.to.parse()?)
+        .subject("Teamwork : missing time")
+        .body(message.to_string())?;
+
+    let mut transport = SmtpTransport::relay(&smtp.server)?.port(smtp.port);
+    if let (Some(username), Some(password)) = (&smtp.username, &smtp.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+    transport.build().send(&email)?;
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use crate::teamwork_config::QuietHours;
+
+    use super::*;
+
+    #[test]
+    fn test_next_check_skips_quiet_hours() {
+        let time = |h: u32, m: u32| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2019, 7, d).unwrap();
+        let reminder = ReminderConfig {
+            times: vec![time(17, 30), time(9, 0), time(22, 0)],
+            quiet_hours: Some(QuietHours { start: time(20, 0), end: time(8, 0) }),
+            channels: vec![],
+        };
+
+        assert_eq!(next_check(day(1).and_time(time(8, 0)), &reminder), Some(day(1).and_time(time(9, 0))));
+        assert_eq!(next_check(day(1).and_time(time(9, 0)), &reminder), Some(day(1).and_time(time(17, 30))));
+        assert_eq!(next_check(day(1).and_time(time(18, 0)), &reminder), Some(day(2).and_time(time(9, 0))));
+
+        assert!(QuietHours { start: time(20, 0), end: time(8, 0) }.contains(time(23, 0)));
+        assert!(!QuietHours { start: time(12, 0), end: time(14, 0) }.contains(time(14, 0)));
+    }
+}
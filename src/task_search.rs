@@ -0,0 +1,75 @@
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+
+use crate::teamwork_service::Task;
+
+const STARRED_BONUS: i64 = 60;
+const RECENT_BONUS: i64 = 40;
+
+/// Sorts the tasks matching `query` by relevance, starred and recently used tasks first on equal footing.
+/// `recent` is ordered from the most recently used task.
+pub fn rank_tasks<'a>(tasks: &'a [Task], query: &str, starred: &[usize], recent: &[usize]) -> Vec<&'a Task> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+
+    let mut scored: Vec<(i64, &Task)> = tasks.iter()
+        .filter_map(|t| {
+            let score = match query.trim().is_empty() {
+                true => Some(0),
+                false => matcher.fuzzy_match(&t.to_string(), query.trim()),
+            }?;
+
+            Some((score + bonus(t, starred, recent), t))
+        })
+        .collect();
+
+    scored.sort_by(|(s1, t1), (s2, t2)| s2.cmp(s1).then_with(|| t1.name.cmp(&t2.name)));
+
+    return scored.into_iter().map(|(_, t)| t).collect();
+}
+
+fn bonus(task: &Task, starred: &[usize], recent: &[usize]) -> i64 {
+    let starred_bonus = match starred.contains(&task.id) {
+        true => STARRED_BONUS,
+        false => 0,
+    };
+
+    let recent_bonus = recent.iter()
+        .position(|id| id == &task.id)
+        .map(|rank| RECENT_BONUS * (recent.len() - rank) as i64 / recent.len() as i64)
+        .unwrap_or(0);
+
+    return starred_bonus + recent_bonus;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: usize, project: &str, name: &str) -> Task {
+        return Task {
+            id,
+            project_name: project.to_string(),
+            todo_list_name: "Run".to_string(),
+            todo_list_id: None,
+            parent_task: None,
+            name: name.to_string(),
+            sub_tasks: vec![],
+        };
+    }
+
+    #[test]
+    fn test_starred_and_recent_tasks_come_first() {
+        let tasks = vec![
+            task(1, "Acme", "Support"),
+            task(2, "Globex", "Support"),
+            task(3, "Initech", "Support"),
+            task(4, "Acme", "Development"),
+        ];
+
+        let ranked: Vec<usize> = rank_tasks(&tasks, "support", &[3], &[2]).iter().map(|t| t.id).collect();
+        assert_eq!(ranked, vec![3, 2, 1]);
+
+        let ranked: Vec<usize> = rank_tasks(&tasks, "acme dev", &[3], &[2]).iter().map(|t| t.id).collect();
+        assert_eq!(ranked, vec![4]);
+    }
+}
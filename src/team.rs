@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::slice::Iter;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::parsing::week_start;
+use crate::teamwork_config::{TimeOff, WorkSchedule};
+use crate::teamwork_service::{minutes_to_hours, Person, TimeEntry};
+
+/// 8 hours from monday to friday.
+pub const DEFAULT_SCHEDULE: [i32; 7] = [8, 8, 8, 8, 8, 0, 0];
+
+/// Missing hours of a person, by week.
+pub struct PersonMissing {
+    pub person: Person,
+    /// Missing hours keyed by the monday of each week
+    pub weeks: BTreeMap<NaiveDate, i32>,
+}
+
+impl PersonMissing {
+    pub fn total(&self) -> i32 {
+        return self.weeks.values().sum();
+    }
+}
+
+pub fn schedule_of<'a>(person_id: &str, schedules: &'a [WorkSchedule]) -> &'a [i32; 7] {
+    return schedules.iter()
+        .find(|s| s.person_id == person_id)
+        .map(|s| &s.hours)
+        .unwrap_or(&DEFAULT_SCHEDULE);
+}
+
+/// Hours missing each week between `since` and `until` (excluded) :
+/// for every day, the scheduled hours minus the logged time and the time off.
+pub fn missing_by_week(
+    since: NaiveDate,
+    until: NaiveDate,
+    entries: &[&TimeEntry],
+    schedule: &[i32; 7],
+    times_off: &Iter<TimeOff>,
+) -> BTreeMap<NaiveDate, i32> {
+    let mut weeks = BTreeMap::new();
+
+    let mut date = since;
+    while date < until {
+        let day = date.weekday().num_days_from_monday() as usize;
+        let logged_minutes: i32 = entries.iter()
+            .filter(|e| e.date.date_naive() == date)
+            .map(|e| e.minutes())
+            .sum();
+        let off: i32 = times_off.clone()
+            .filter(|t| t.date == date.format("%Y-%m-%d").to_string())
+            .map(|t| t.hours)
+            .sum();
+
+        let missing_minutes = ((schedule[day] - off) * 60 - logged_minutes).max(0);
+        *weeks.entry(week_start(date)).or_insert(0) += minutes_to_hours(missing_minutes);
+
+        date = date.succ_opt().unwrap();
+    }
+
+    return weeks;
+}
+
+/// One line per person, one column per week.
+pub fn to_csv(report: &[PersonMissing]) -> String {
+    let weeks = report.iter()
+        .flat_map(|p| p.weeks.keys())
+        .collect::<std::collections::BTreeSet<_>>();
+
+    let mut header = vec!["id".to_string(), "name".to_string(), "email".to_string()];
+    header.extend(weeks.iter().map(|w| w.format("%Y-%m-%d").to_string()));
+    header.push("total".to_string());
+
+    let mut lines = vec![header.join(",")];
+    for p in report {
+        let mut cells = vec![csv_cell(&p.person.id), csv_cell(&p.person.name()), csv_cell(&p.person.email)];
+        cells.extend(weeks.iter().map(|w| p.weeks.get(w).unwrap_or(&0).to_string()));
+        cells.push(p.total().to_string());
+        lines.push(cells.join(","));
+    }
+
+    return lines.join("\n");
+}
+
+fn csv_cell(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    return value.to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(date: NaiveDate, hours: i32) -> TimeEntry {
+        return TimeEntry { person_id: "42".to_string(), ..TimeEntry::logged_on(date, hours, "") };
+    }
+
+    #[test]
+    fn test_missing_by_week_and_csv() {
+        let d = |day: u32| NaiveDate::from_ymd_opt(2019, 7, day).unwrap();
+        // From wednesday 3rd to tuesday 9th included
+        let entries = [entry(d(3), 8), entry(d(4), 5), entry(d(8), 10)];
+        let times_off = [TimeOff { date: "2019-07-05".to_string(), hours: 4 }];
+        let schedule = [8, 8, 8, 8, 4, 0, 0];
+
+        let weeks = missing_by_week(d(3), d(10), &entries.iter().collect::<Vec<_>>(), &schedule, &times_off.iter());
+
+        assert_eq!(weeks.into_iter().collect::<Vec<_>>(), vec![(d(1), 3), (d(8), 8)]);
+
+        let report = vec![PersonMissing {
+            person: Person {
+                id: "42".to_string(),
+                first_name: "Jane".to_string(),
+                last_name: "Doe, Jr".to_string(),
+                email: "jane@example.com".to_string(),
+            },
+            weeks: vec![(d(1), 3), (d(8), 8)].into_iter().collect(),
+        }];
+
+        assert_eq!(to_csv(&report), "id,name,email,2019-07-01,2019-07-08,total\n42,\"Jane Doe, Jr\",jane@example.com,3,8,11");
+    }
+}
@@ -3,11 +3,16 @@ use std::error::Error;
 use std::fmt;
 use std::fs;
 use std::hash::Hash;
-use std::io::Result as IoResult;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
 use std::path::PathBuf;
+use std::str::FromStr;
 
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use log::{debug, log_enabled, trace, Level};
 use serde::{Deserialize, Serialize};
 
+use crate::parsing::week_start;
+
 #[derive(Debug, Clone)]
 pub struct NoConfigError;
 
@@ -23,25 +28,104 @@ impl Error for NoConfigError {
     }
 }
 
-#[derive(Deserialize, Clone, Serialize, Debug)]
+#[derive(Debug, Clone)]
+pub struct UnknownAliasError {
+    pub kind: Option<AliasKind>,
+    pub alias: String,
+}
+
+impl fmt::Display for UnknownAliasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            Some(kind) => write!(f, "{} is neither a {} id nor a {} alias", self.alias, kind, kind),
+            None => write!(f, "no alias named {}", self.alias),
+        }
+    }
+}
+
+impl Error for UnknownAliasError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+/// Configuration of the command line, saved in ~/.teamwork.
+#[derive(Deserialize, Clone, Serialize, Debug, Default)]
 pub struct TeamWorkConfig {
     pub company_id: String,
     pub token: String,
-    pub project_aliases: Vec<ProjectAlias>,
+    pub aliases: Vec<Alias>,
     pub times_off: Vec<TimeOff>,
     pub starred_tasks: Vec<usize>,
+    pub timer_rounding: Option<TimerRounding>,
+    pub templates: Vec<TimeEntryTemplate>,
+    pub git: Option<GitConfig>,
+    pub cache_ttl_minutes: Option<i64>,
+    /// Ids of the projects whose time entries are billable by default
+    pub billable_projects: Vec<String>,
+    /// Working hours of the people of the team, 8 hours from monday to friday when missing
+    pub work_schedules: Vec<WorkSchedule>,
+    pub reminder: Option<ReminderConfig>,
 }
 
 impl TeamWorkConfig {
-    pub fn get_alias(&self, project_id: &String) -> Option<&ProjectAlias> {
-        return self.project_aliases.iter()
-            .find(|a| a.project_id.as_str() == project_id.as_str());
+    pub fn get_alias(&self, kind: AliasKind, id: &str) -> Option<&Alias> {
+        return self.aliases.iter()
+            .find(|a| a.kind == kind && a.id == id);
+    }
+
+    pub fn find_alias(&self, alias: &str) -> Option<&Alias> {
+        return self.aliases.iter()
+            .find(|a| a.alias == alias);
+    }
+
+    /// Returns the id behind `id_or_alias` : either an alias of the given kind, or a numeric id.
+    pub fn resolve_id(&self, kind: AliasKind, id_or_alias: &str) -> Result<String, UnknownAliasError> {
+        if let Some(a) = self.aliases.iter().find(|a| a.kind == kind && a.alias == id_or_alias) {
+            return Ok(a.id.clone());
+        }
+
+        if !id_or_alias.is_empty() && id_or_alias.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(id_or_alias.to_string());
+        }
+
+        return Err(UnknownAliasError { kind: Some(kind), alias: id_or_alias.to_string() });
+    }
+
+    /// Adds an alias, replacing any alias with the same name or pointing to the same item.
+    pub fn with_alias(&self, kind: AliasKind, id: &str, alias: &str) -> TeamWorkConfig {
+        let mut new = self.clone();
+        new.aliases.retain(|a| a.alias != alias && !(a.kind == kind && a.id == id));
+        new.aliases.push(Alias {
+            kind,
+            id: id.to_string(),
+            alias: alias.to_string(),
+        });
+
+        return new;
+    }
+
+    pub fn without_alias(&self, alias: &str) -> Result<TeamWorkConfig, UnknownAliasError> {
+        self.find_alias(alias)
+            .ok_or_else(|| UnknownAliasError { kind: None, alias: alias.to_string() })?;
+
+        let mut new = self.clone();
+        new.aliases.retain(|a| a.alias != alias);
+
+        return Ok(new);
+    }
+
+    pub fn with_renamed_alias(&self, alias: &str, new_alias: &str) -> Result<TeamWorkConfig, UnknownAliasError> {
+        let existing = self.find_alias(alias)
+            .ok_or_else(|| UnknownAliasError { kind: None, alias: alias.to_string() })?;
+
+        return Ok(self.with_alias(existing.kind, &existing.id.clone(), new_alias));
     }
 
     pub fn with_time_off(&self, date: String, hours: i32) -> TeamWorkConfig {
         let off = TimeOff {
             date: date.clone(),
-            hours: hours.clone(),
+            hours,
         };
         let mut new = self.clone();
         let mut times_off = new.times_off;
@@ -55,15 +139,57 @@ impl TeamWorkConfig {
 
         return new;
     }
+
+    pub fn with_billable_project(&self, project_id: &str, billable: bool) -> TeamWorkConfig {
+        let mut new = self.clone();
+        new.billable_projects.retain(|p| p != project_id);
+        if billable {
+            new.billable_projects.push(project_id.to_string());
+        }
+
+        return new;
+    }
+
+    /// Sets the working hours of a person, replacing the previous ones.
+    pub fn with_work_schedule(&self, schedule: WorkSchedule) -> TeamWorkConfig {
+        let mut new = self.clone();
+        new.work_schedules.retain(|s| s.person_id != schedule.person_id);
+        new.work_schedules.push(schedule);
+
+        return new;
+    }
+
+    /// Adds a template, replacing any template with the same name.
+    pub fn with_template(&self, template: TimeEntryTemplate) -> TeamWorkConfig {
+        let mut new = self.clone();
+        new.templates.retain(|t| t.name != template.name);
+        new.templates.push(template);
+
+        return new;
+    }
+
+    pub fn without_template(&self, name: &str) -> TeamWorkConfig {
+        let mut new = self.clone();
+        new.templates.retain(|t| t.name != name);
+
+        return new;
+    }
 }
 
 impl PartialEq<TeamWorkConfig> for TeamWorkConfig {
     fn eq(&self, other: &TeamWorkConfig) -> bool {
         *self.company_id == other.company_id
             && *self.token == other.token
-            && array_eq(&*self.times_off, &other.times_off)
-            && array_eq(&*self.starred_tasks, &other.starred_tasks)
-            && array_eq(&*self.project_aliases, &other.project_aliases)
+            && array_eq(&self.times_off, &other.times_off)
+            && array_eq(&self.starred_tasks, &other.starred_tasks)
+            && array_eq(&self.aliases, &other.aliases)
+            && self.timer_rounding == other.timer_rounding
+            && array_eq(&self.templates, &other.templates)
+            && self.git == other.git
+            && self.cache_ttl_minutes == other.cache_ttl_minutes
+            && array_eq(&self.billable_projects, &other.billable_projects)
+            && array_eq(&self.work_schedules, &other.work_schedules)
+            && self.reminder == other.reminder
     }
 }
 
@@ -79,6 +205,235 @@ fn array_eq<T>(a: &[T], b: &[T]) -> bool where T: Eq + Hash,
     a_hash == b_hash
 }
 
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum AliasKind {
+    Project,
+    Tasklist,
+    Task,
+}
+
+impl fmt::Display for AliasKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let str = match self {
+            AliasKind::Project => "project",
+            AliasKind::Tasklist => "tasklist",
+            AliasKind::Task => "task",
+        };
+
+        return write!(f, "{}", str);
+    }
+}
+
+impl FromStr for AliasKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "project" => Ok(AliasKind::Project),
+            "tasklist" => Ok(AliasKind::Tasklist),
+            "task" => Ok(AliasKind::Task),
+            _ => Err(format!("{} is not an alias kind, expected project, tasklist or task", s)),
+        };
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Alias {
+    pub kind: AliasKind,
+    pub id: String,
+    pub alias: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingRule {
+    Up,
+    Down,
+    Nearest,
+}
+
+impl FromStr for RoundingRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "up" => Ok(RoundingRule::Up),
+            "down" => Ok(RoundingRule::Down),
+            "nearest" => Ok(RoundingRule::Nearest),
+            _ => Err(format!("{} is not a rounding rule, expected up, down or nearest", s)),
+        };
+    }
+}
+
+/// How the elapsed time of a timer is rounded before being saved as a time entry.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimerRounding {
+    pub rule: RoundingRule,
+    pub minutes: i64,
+}
+
+impl Default for TimerRounding {
+    fn default() -> Self {
+        return TimerRounding {
+            rule: RoundingRule::Nearest,
+            minutes: 1,
+        };
+    }
+}
+
+impl TimerRounding {
+    /// Rounds a number of seconds to a multiple of `minutes`, returning minutes.
+    pub fn round(&self, seconds: i64) -> i64 {
+        let step = self.minutes.max(1) * 60;
+        let steps = match self.rule {
+            RoundingRule::Up => (seconds + step - 1) / step,
+            RoundingRule::Down => seconds / step,
+            RoundingRule::Nearest => (seconds + step / 2) / step,
+        };
+
+        return steps * step / 60;
+    }
+}
+
+/// A time entry logged again and again, for example a daily stand-up.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimeEntryTemplate {
+    pub name: String,
+    /// Task id or alias
+    pub task: String,
+    pub minutes: i64,
+    pub description: String,
+    /// Days the entry is logged on, every working day when empty.
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    /// The entry is logged one week out of `every_weeks`, counted from the week of `since`.
+    #[serde(default = "default_every_weeks")]
+    pub every_weeks: u32,
+    #[serde(default)]
+    pub since: Option<String>,
+}
+
+fn default_every_weeks() -> u32 {
+    return 1;
+}
+
+impl TimeEntryTemplate {
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        let on_weekday = match self.weekdays.is_empty() {
+            true => date.weekday() != Weekday::Sat && date.weekday() != Weekday::Sun,
+            false => self.weekdays.contains(&date.weekday()),
+        };
+        if !on_weekday {
+            return false;
+        }
+
+        let since = self.since.as_ref()
+            .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok());
+
+        return match since {
+            Some(since) if date < since => false,
+            Some(since) => {
+                let weeks = (week_start(date) - week_start(since)).num_weeks();
+                weeks % i64::from(self.every_weeks.max(1)) == 0
+            }
+            None => true,
+        };
+    }
+}
+
+/// Where to look for commits when proposing time entries from the git history.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GitConfig {
+    /// Author of the commits, the `user.email` of each repository when missing.
+    pub author: Option<String>,
+    pub repositories: Vec<String>,
+    pub branch_patterns: Vec<BranchPattern>,
+}
+
+/// When and how to remind you of the missing time.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ReminderConfig {
+    /// Times of the day the missing time is checked, when running as a daemon
+    pub times: Vec<NaiveTime>,
+    pub quiet_hours: Option<QuietHours>,
+    pub channels: Vec<NotificationChannel>,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        return ReminderConfig {
+            times: vec![NaiveTime::from_hms_opt(17, 0, 0).unwrap()],
+            quiet_hours: None,
+            channels: vec![NotificationChannel::Desktop],
+        };
+    }
+}
+
+/// No notification between `start` and `end`, possibly over midnight.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            return self.start <= time && time < self.end;
+        }
+        return self.start <= time || time < self.end;
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum NotificationChannel {
+    Desktop,
+    Bell,
+    Email(SmtpConfig),
+    Webhook { url: String },
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]
+pub struct SmtpConfig {
+    pub server: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: String,
+}
+
+// Written by hand for the password not to end up in logs and error messages
+impl fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_struct("SmtpConfig")
+            .field("server", &self.server)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish();
+    }
+}
+
+/// Hours a person works on each day of the week, from monday to sunday.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WorkSchedule {
+    pub person_id: String,
+    pub hours: [i32; 7],
+}
+
+/// Maps the branches matching the `pattern` regex to a task id or alias.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BranchPattern {
+    pub pattern: String,
+    pub task: String,
+}
+
+/// Alias format used before task lists and tasks could be aliased.
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ProjectAlias {
     pub project_id: String,
@@ -91,50 +446,90 @@ pub struct TimeOff {
     pub hours: i32,
 }
 
+/// Reads ~/.teamwork, `None` when it does not exist yet.
 pub fn get_config() -> Result<Option<TeamWorkConfig>, Box<dyn Error>> {
-    let path = get_teamwork_file();
+    let path = get_teamwork_file()?;
     return get_config_from_path(&path);
 }
 
 pub fn get_config_from_path(file_path: &PathBuf) -> Result<Option<TeamWorkConfig>, Box<dyn Error>> {
     if !file_path.exists() {
+        debug!("No config at {}", file_path.display());
         return Ok(None);
     }
 
     let file_content = fs::read_to_string(file_path)?;
+    debug!("Config read from {}", file_path.display());
 
     let serializable_config: SerializableTeamWorkConfig = serde_json::from_str(&file_content)?;
     let config = TeamWorkConfig::from(serializable_config);
+    if log_enabled!(Level::Trace) {
+        trace!("Config : {}", redacted_config(&config));
+    }
 
     return Ok(Some(config));
 }
 
-pub fn save_token_and_company(company_id: &String, token: &String) {
+/// The config as it is saved, the token and the SMTP passwords masked, to be logged.
+pub fn redacted_config(config: &TeamWorkConfig) -> String {
+    let mut redacted = config.clone();
+    redacted.token = "***".to_string();
+    if let Some(reminder) = redacted.reminder.as_mut() {
+        for channel in reminder.channels.iter_mut() {
+            if let NotificationChannel::Email(smtp) = channel {
+                smtp.password = smtp.password.as_ref().map(|_| "***".to_string());
+            }
+        }
+    }
+
+    return serde_json::to_string(&SerializableTeamWorkConfig::from(&redacted))
+        .unwrap_or_default();
+}
+
+pub fn save_token_and_company(company_id: &str, token: &str) {
     let config = TeamWorkConfig {
-        company_id: company_id.clone(),
-        token: token.clone(),
-        project_aliases: vec![],
-        times_off: vec![],
-        starred_tasks: vec![],
+        company_id: company_id.to_string(),
+        token: token.to_string(),
+        ..Default::default()
     };
     save_config(&config);
 }
 
-pub fn save_alias(project_id: &String, alias: &String) -> Result<TeamWorkConfig, Box<dyn Error>> {
+pub fn save_alias(kind: AliasKind, id: &str, alias: &str) -> Result<TeamWorkConfig, Box<dyn Error>> {
     match get_config() {
         Ok(config) => match config {
             Some(c) => {
-                let new_alias = ProjectAlias {
-                    project_id: project_id.clone(),
-                    alias: alias.clone(),
-                };
-                let mut aliases = c.project_aliases.to_vec();
-                aliases.push(new_alias);
+                let tc = c.with_alias(kind, id, alias);
 
-                let tc = TeamWorkConfig {
-                    project_aliases: aliases,
-                    ..c
-                };
+                save_config(&tc);
+                Ok(tc)
+            }
+            None => Err(Box::new(NoConfigError)),
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn remove_alias(alias: &str) -> Result<TeamWorkConfig, Box<dyn Error>> {
+    match get_config() {
+        Ok(config) => match config {
+            Some(c) => {
+                let tc = c.without_alias(alias)?;
+
+                save_config(&tc);
+                Ok(tc)
+            }
+            None => Err(Box::new(NoConfigError)),
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn rename_alias(alias: &str, new_alias: &str) -> Result<TeamWorkConfig, Box<dyn Error>> {
+    match get_config() {
+        Ok(config) => match config {
+            Some(c) => {
+                let tc = c.with_renamed_alias(alias, new_alias)?;
 
                 save_config(&tc);
                 Ok(tc)
@@ -204,11 +599,13 @@ pub fn unstar_task(task_id: &usize) -> Result<(), Box<dyn Error>> {
 }
 
 pub fn save_config(config: &TeamWorkConfig) {
-    save_config_to_path(config, &get_teamwork_file())
+    get_teamwork_file()
+        .and_then(|path| save_config_to_path(config, &path))
         .expect("Unable to write file ~/.teamwork");
 }
 
 fn save_config_to_path(config: &TeamWorkConfig, path: &PathBuf) -> IoResult<()> {
+    debug!("Config saved to {}", path.display());
     let serializable_config = SerializableTeamWorkConfig::from(config);
 
     let toml = serde_json::to_string_pretty(&serializable_config)
@@ -217,20 +614,36 @@ fn save_config_to_path(config: &TeamWorkConfig, path: &PathBuf) -> IoResult<()>
     return fs::write(path, toml);
 }
 
-fn get_teamwork_file() -> PathBuf {
+fn get_teamwork_file() -> IoResult<PathBuf> {
     let home_dir = dirs::home_dir()
-        .expect("Could not get your home dir");
+        .ok_or_else(|| IoError::new(ErrorKind::NotFound, "Could not get your home dir"))?;
 
-    return home_dir.join(".teamwork");
+    return Ok(home_dir.join(".teamwork"));
 }
 
 #[derive(Deserialize, Clone, Serialize)]
 pub struct SerializableTeamWorkConfig {
     pub company_id: String,
     pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     project_aliases: Option<Vec<ProjectAlias>>,
+    aliases: Option<Vec<Alias>>,
     times_off: Option<Vec<TimeOff>>,
     starred_tasks: Option<Vec<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timer_rounding: Option<TimerRounding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    templates: Option<Vec<TimeEntryTemplate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git: Option<GitConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_ttl_minutes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    billable_projects: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    work_schedules: Option<Vec<WorkSchedule>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reminder: Option<ReminderConfig>,
 }
 
 impl From<&TeamWorkConfig> for SerializableTeamWorkConfig {
@@ -240,9 +653,17 @@ impl From<&TeamWorkConfig> for SerializableTeamWorkConfig {
         return SerializableTeamWorkConfig {
             company_id: c.company_id,
             token: c.token,
-            project_aliases: Some(c.project_aliases),
+            project_aliases: None,
+            aliases: Some(c.aliases),
             times_off: Some(c.times_off),
             starred_tasks: Some(c.starred_tasks),
+            timer_rounding: c.timer_rounding,
+            templates: Some(c.templates).filter(|t| !t.is_empty()),
+            git: c.git,
+            cache_ttl_minutes: c.cache_ttl_minutes,
+            billable_projects: Some(c.billable_projects).filter(|p| !p.is_empty()),
+            work_schedules: Some(c.work_schedules).filter(|s| !s.is_empty()),
+            reminder: c.reminder,
         };
     }
 }
@@ -252,13 +673,33 @@ impl From<SerializableTeamWorkConfig> for TeamWorkConfig {
         return TeamWorkConfig {
             company_id: config.company_id,
             token: config.token,
-            project_aliases: config.project_aliases.unwrap_or_else(|| vec![]),
-            times_off: config.times_off.unwrap_or_else(|| vec![]),
-            starred_tasks: config.starred_tasks.unwrap_or_else(|| vec![]),
+            aliases: merge_aliases(config.project_aliases, config.aliases),
+            times_off: config.times_off.unwrap_or_default(),
+            starred_tasks: config.starred_tasks.unwrap_or_default(),
+            timer_rounding: config.timer_rounding,
+            templates: config.templates.unwrap_or_default(),
+            git: config.git,
+            cache_ttl_minutes: config.cache_ttl_minutes,
+            billable_projects: config.billable_projects.unwrap_or_default(),
+            work_schedules: config.work_schedules.unwrap_or_default(),
+            reminder: config.reminder,
         };
     }
 }
 
+fn merge_aliases(project_aliases: Option<Vec<ProjectAlias>>, aliases: Option<Vec<Alias>>) -> Vec<Alias> {
+    let mut merged: Vec<Alias> = project_aliases.unwrap_or_default().into_iter()
+        .map(|a| Alias {
+            kind: AliasKind::Project,
+            id: a.project_id,
+            alias: a.alias,
+        })
+        .collect();
+    merged.extend(aliases.unwrap_or_default());
+
+    return merged;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,14 +712,16 @@ mod tests {
         let config = TeamWorkConfig {
             company_id: "test-company-id".to_string(),
             token: "test-token".to_string(),
-            project_aliases: vec![
-                ProjectAlias {
+            aliases: vec![
+                Alias {
+                    kind: AliasKind::Project,
+                    id: "project-id-1".to_string(),
                     alias: "project-alias-1".to_string(),
-                    project_id: "project-id-1".to_string(),
                 },
-                ProjectAlias {
-                    alias: "project-alias-1".to_string(),
-                    project_id: "project-id-1".to_string(),
+                Alias {
+                    kind: AliasKind::Task,
+                    id: "124343".to_string(),
+                    alias: "task-alias-1".to_string(),
                 }
             ],
             starred_tasks: vec![124343, 24543543],
@@ -292,24 +735,27 @@ mod tests {
                     hours: 4,
                 }
             ],
+            ..Default::default()
         };
 
         let result = save_config_to_path(&config, &output_path);
 
-        assert!(!result.is_err(), "{} should have been writen without error, but got {:#?}", output_path.to_str().unwrap(), result.err());
+        assert!(result.is_ok(), "{} should have been writen without error, but got {:#?}", output_path.to_str().unwrap(), result.err());
 
-        let result_content = fs::read_to_string(output_path);
+        let result_content = fs::read_to_string(&output_path);
         let expected_content = "{
   \"company_id\": \"test-company-id\",
   \"token\": \"test-token\",
-  \"project_aliases\": [
+  \"aliases\": [
     {
-      \"project_id\": \"project-id-1\",
+      \"kind\": \"project\",
+      \"id\": \"project-id-1\",
       \"alias\": \"project-alias-1\"
     },
     {
-      \"project_id\": \"project-id-1\",
-      \"alias\": \"project-alias-1\"
+      \"kind\": \"task\",
+      \"id\": \"124343\",
+      \"alias\": \"task-alias-1\"
     }
   ],
   \"times_off\": [
@@ -329,6 +775,10 @@ mod tests {
 }";
 
         assert_eq!(result_content.unwrap(), expected_content);
+        assert_eq!(get_config_from_path(&output_path).unwrap().unwrap(), config);
+        assert_ne!(TeamWorkConfig { billable_projects: vec!["1".to_string()], ..config.clone() }, config);
+        assert_ne!(TeamWorkConfig { work_schedules: vec![WorkSchedule { person_id: "7".to_string(), hours: [8; 7] }], ..config.clone() }, config);
+        assert_ne!(TeamWorkConfig { reminder: Some(ReminderConfig::default()), ..config.clone() }, config);
     }
 
     #[test]
@@ -369,7 +819,7 @@ mod tests {
 
         let result = get_config_from_path(&output_path);
 
-        assert!(!result.is_err(), "should have read config from {}, but got {:#?}", output_path.to_str().unwrap(), result.err());
+        assert!(result.is_ok(), "should have read config from {}, but got {:#?}", output_path.to_str().unwrap(), result.err());
 
         let success = result.unwrap();
         assert!(success.is_some(), "should have existing config");
@@ -377,14 +827,16 @@ mod tests {
         let config = TeamWorkConfig {
             company_id: "test-company-id".to_string(),
             token: "test-token".to_string(),
-            project_aliases: vec![
-                ProjectAlias {
+            aliases: vec![
+                Alias {
+                    kind: AliasKind::Project,
+                    id: "project-id-1".to_string(),
                     alias: "project-alias-1".to_string(),
-                    project_id: "project-id-1".to_string(),
                 },
-                ProjectAlias {
+                Alias {
+                    kind: AliasKind::Project,
+                    id: "project-id-1".to_string(),
                     alias: "project-alias-1".to_string(),
-                    project_id: "project-id-1".to_string(),
                 }
             ],
             starred_tasks: vec![124343, 24543543],
@@ -398,8 +850,101 @@ mod tests {
                     hours: 4,
                 }
             ],
+            ..Default::default()
         };
 
         assert_eq!(success.unwrap(), config);
     }
+
+    #[test]
+    fn test_alias_replaces_same_name_or_same_item() {
+        let config = TeamWorkConfig {
+            company_id: "test-company-id".to_string(),
+            token: "test-token".to_string(),
+            ..Default::default()
+        };
+
+        let config = config
+            .with_alias(AliasKind::Project, "1", "proj")
+            .with_alias(AliasKind::Task, "2", "dev")
+            .with_alias(AliasKind::Task, "3", "dev")
+            .with_alias(AliasKind::Project, "1", "client");
+
+        assert_eq!(config.aliases, vec![
+            Alias { kind: AliasKind::Task, id: "3".to_string(), alias: "dev".to_string() },
+            Alias { kind: AliasKind::Project, id: "1".to_string(), alias: "client".to_string() },
+        ]);
+
+        assert_eq!(config.resolve_id(AliasKind::Task, "dev").unwrap(), "3");
+        assert_eq!(config.resolve_id(AliasKind::Task, "42").unwrap(), "42");
+        assert!(config.resolve_id(AliasKind::Task, "client").is_err());
+
+        let renamed = config.with_renamed_alias("dev", "support").unwrap();
+        assert_eq!(renamed.resolve_id(AliasKind::Task, "support").unwrap(), "3");
+        assert!(renamed.without_alias("dev").is_err());
+    }
+
+    #[test]
+    fn test_timer_rounding() {
+        let up = TimerRounding { rule: RoundingRule::Up, minutes: 15 };
+        let down = TimerRounding { rule: RoundingRule::Down, minutes: 15 };
+        let nearest = TimerRounding { rule: RoundingRule::Nearest, minutes: 15 };
+
+        assert_eq!(up.round(16 * 60), 30);
+        assert_eq!(down.round(29 * 60), 15);
+        assert_eq!(nearest.round(22 * 60), 15);
+        assert_eq!(nearest.round(23 * 60), 30);
+        assert_eq!(TimerRounding::default().round(90 * 60 + 40), 91);
+    }
+
+    #[test]
+    fn test_template_occurrences() {
+        let template = TimeEntryTemplate {
+            name: "sprint-review".to_string(),
+            task: "42".to_string(),
+            minutes: 60,
+            description: "Sprint review".to_string(),
+            weekdays: vec![Weekday::Fri],
+            every_weeks: 2,
+            since: Some("2020-01-06".to_string()),
+        };
+
+        assert!(template.occurs_on(NaiveDate::from_ymd_opt(2020, 1, 10).unwrap()));
+        assert!(!template.occurs_on(NaiveDate::from_ymd_opt(2020, 1, 9).unwrap()));
+        assert!(!template.occurs_on(NaiveDate::from_ymd_opt(2020, 1, 17).unwrap()));
+        assert!(template.occurs_on(NaiveDate::from_ymd_opt(2020, 1, 24).unwrap()));
+        assert!(!template.occurs_on(NaiveDate::from_ymd_opt(2020, 1, 3).unwrap()));
+    }
+
+    #[test]
+    fn test_secrets_are_not_logged() {
+        let mut path = std::env::temp_dir();
+        path.push(".teamwork-cli-config_test_secrets_are_not_logged-4e3a9d0c-7b1f-4f6e-a2c8-5d9b0e1f2a37.json");
+        fs::write(&path, r#"{
+            "company_id": "company",
+            "token": "api-token",
+            "reminder": {
+                "times": ["17:00:00"],
+                "quiet_hours": null,
+                "channels": [{
+                    "kind": "email",
+                    "server": "smtp.example.com",
+                    "port": 587,
+                    "username": "me",
+                    "password": "smtp-password",
+                    "from": "me@example.com",
+                    "to": "me@example.com"
+                }]
+            }
+        }"#).unwrap();
+
+        let config = get_config_from_path(&path).unwrap().unwrap();
+        let _ = fs::remove_file(&path);
+
+        let logged = redacted_config(&config);
+        assert!(logged.contains("smtp.example.com"));
+        assert!(!logged.contains("api-token"));
+        assert!(!logged.contains("smtp-password"));
+        assert!(!format!("{:?}", config.reminder).contains("smtp-password"));
+    }
 }
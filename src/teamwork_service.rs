@@ -1,71 +1,348 @@
-use std::borrow::Borrow;
+use std::fmt;
 
-use chrono::{Datelike, DateTime, NaiveDate, Utc, Weekday};
-use reqwest::Url;
+use chrono::{Datelike, DateTime, Duration, NaiveDate, NaiveDateTime, Utc, Weekday};
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde::de::DeserializeOwned;
 
-use crate::teamwork_config::{TeamWorkConfig, TimeOff};
+use crate::async_service::AsyncTeamWorkService;
+use crate::cache::{Cache, DEFAULT_CACHE_TTL_MINUTES};
+use crate::transport::{transport_from_env, ApiError, ApiRequest, Transport};
+use crate::teamwork_config::{TeamWorkConfig, TimeEntryTemplate, TimeOff};
+use crate::timer::Timer;
 use std::slice::Iter;
+use std::sync::Arc;
 
 const WORKING_DAY_DURATION: i32 = 8;
 
+/// Whole hours closest to `minutes`, the workload being counted in hours.
+pub fn minutes_to_hours(minutes: i32) -> i32 {
+    return (minutes + 30).div_euclid(60);
+}
+
+/// Client of the Teamwork API for the account of a configuration.
+///
+/// It owns its credentials, so it can be cloned and moved to other threads freely.
+/// Projects, task lists and tasks are cached on disk, see [`Cache`].
 #[derive(Clone)]
-pub struct TeamWorkService<'a> {
-    client: HttpClient<'a>,
+pub struct TeamWorkService {
+    client: HttpClient,
+    async_client: AsyncTeamWorkService,
+    cache: Arc<Cache>,
+    queued_entries: Vec<TimeEntry>,
+    billable_projects: Vec<String>,
 }
 
-impl<'a> TeamWorkService<'a> {
+impl TeamWorkService {
+    /// Client reaching Teamwork over HTTP, unless `TEAMWORK_REPLAY` or `TEAMWORK_RECORD` is set,
+    /// with its cache kept in ~/.teamwork-cache.
     pub fn new(config: &TeamWorkConfig) -> TeamWorkService {
-        let client = HttpClient::new(config);
+        let ttl = config.cache_ttl_minutes.unwrap_or(DEFAULT_CACHE_TTL_MINUTES);
+        let cache = Cache::load(&config.company_id, ttl);
+
+        return TeamWorkService::with_transport(config, transport_from_env(config)).with_cache(cache);
+    }
+
+    /// Client sending its requests through `transport`, to replay recorded exchanges for instance.
+    /// Its cache is kept in memory, see [`TeamWorkService::with_cache`].
+    pub fn with_transport(config: &TeamWorkConfig, transport: Arc<dyn Transport>) -> TeamWorkService {
+        let client = HttpClient { transport: transport.clone() };
+        let async_client = AsyncTeamWorkService::new(transport);
+        let ttl = config.cache_ttl_minutes.unwrap_or(DEFAULT_CACHE_TTL_MINUTES);
+        let cache = Arc::new(Cache::in_memory(&config.company_id, ttl));
+
+        let billable_projects = config.billable_projects.clone();
 
-        return TeamWorkService { client };
+        return TeamWorkService { client, async_client, cache, queued_entries: vec![], billable_projects };
     }
 
-    pub fn get_account(&self) -> Result<Account, reqwest::Error> {
-        let response: AccountResponse = self.client.get("me.json")?;
+    pub fn with_cache(self, cache: Cache) -> TeamWorkService {
+        return TeamWorkService { cache: Arc::new(cache), ..self };
+    }
 
-        return Ok(response.account);
+    /// Counts `queued_entries`, saved offline and not in Teamwork yet, as logged in the workload.
+    pub fn with_queued_entries(self, queued_entries: Vec<TimeEntry>) -> TeamWorkService {
+        return TeamWorkService { queued_entries, ..self };
     }
 
-    pub fn list_project(&self, search_opt: &Option<String>) -> Result<ProjectsResponse, reqwest::Error> {
+    /// Returns the value cached under `key`, or fetches and caches it.
+    fn cached<T, F>(&self, key: &str, fetch: F) -> Result<T, ApiError>
+        where T: Serialize + DeserializeOwned,
+              F: FnOnce() -> Result<T, ApiError>
+    {
+        if let Some(value) = self.cache.get(key) {
+            return Ok(value);
+        }
+
+        let value = fetch()?;
+        self.cache.put(key, &value);
+
+        return Ok(value);
+    }
+
+    /// Returns the values cached under `prefix/id` for each id, fetching the missing ones at once.
+    /// `fetch` returns a value or an error per id, in order. The fetched values are cached in a single write.
+    fn cached_many<I, T, F>(&self, ids: &[I], prefix: &str, fetch: F) -> Result<Vec<Result<T, ApiError>>, ApiError>
+        where I: fmt::Display + Clone,
+              T: Serialize + DeserializeOwned,
+              F: FnOnce(Vec<I>) -> Result<Vec<Result<T, ApiError>>, ApiError>
+    {
+        let mut values: Vec<Option<Result<T, ApiError>>> = ids.iter()
+            .map(|id| self.cache.get(&format!("{}/{}", prefix, id)).map(Ok))
+            .collect();
+        let missing: Vec<I> = ids.iter().zip(values.iter())
+            .filter(|(_, value)| value.is_none())
+            .map(|(id, _)| id.clone())
+            .collect();
+        if missing.is_empty() {
+            return Ok(values.into_iter().flatten().collect());
+        }
+
+        let mut fetched = fetch(missing)?.into_iter();
+        for (id, value) in ids.iter().zip(values.iter_mut()) {
+            if value.is_none() {
+                *value = fetched.next();
+                if let Some(Ok(v)) = value {
+                    self.cache.insert(&format!("{}/{}", prefix, id), v);
+                }
+            }
+        }
+        self.cache.save();
+
+        return Ok(values.into_iter().flatten().collect());
+    }
+
+    /// Like `cached_many`, for fetches which fail as a whole.
+    fn cached_all<I, T, F>(&self, ids: &[I], prefix: &str, fetch: F) -> Result<Vec<T>, ApiError>
+        where I: fmt::Display + Clone,
+              T: Serialize + DeserializeOwned,
+              F: FnOnce(Vec<I>) -> Result<Vec<T>, ApiError>
+    {
+        let values = self.cached_many(ids, prefix, |ids| Ok(fetch(ids)?.into_iter().map(Ok).collect()))?;
+
+        return values.into_iter().collect();
+    }
+
+    pub fn get_account(&self) -> Result<Account, ApiError> {
+        return self.cached("account", || {
+            let response: AccountResponse = self.client.get("me.json")?;
+            Ok(response.account)
+        });
+    }
+
+    pub fn list_project(&self, search_opt: &Option<String>) -> Result<ProjectsResponse, ApiError> {
         let projects: ProjectsResponse = match search_opt {
             Some(search_term) => self.client.get_with_params("projects.json", &[("searchTerm", search_term)])?,
-            None => self.client.get("projects.json")?,
+            None => self.cached("projects", || self.client.get("projects.json"))?,
         };
 
         return Ok(projects);
     }
 
-    pub fn list_tasklists(&self, project: &Project) -> Result<Vec<TaskList>, reqwest::Error> {
-        let url = format!("projects/{}/tasklists.json", project.id);
-        let response: TasklistsResponse = self.client
-            .get(url.as_str())?;
+    pub fn list_tasklists(&self, project_id: &str) -> Result<Vec<TaskList>, ApiError> {
+        let url = format!("projects/{}/tasklists.json", project_id);
 
-        return Ok(response.tasklists);
+        return self.cached(&format!("tasklists/{}", project_id), || {
+            let response: TasklistsResponse = self.client
+                .get(url.as_str())?;
+            Ok(response.tasklists)
+        });
     }
 
-    pub fn list_task(&self, tasklist: &TaskList) -> Result<Vec<Task>, reqwest::Error> {
-        let url = format!("tasklists/{}/tasks.json", tasklist.id);
+    pub fn list_task(&self, tasklist_id: &str) -> Result<Vec<Task>, ApiError> {
+        let key = format!("tasks/{}", tasklist_id);
+        if let Some(tasks) = self.cache.get(&key) {
+            return Ok(tasks);
+        }
+
+        let url = format!("tasklists/{}/tasks.json", tasklist_id);
         let response: TasksResponse = self.client
             .get_with_params(url.as_str(), &[("nestSubTasks", "yes")])?;
 
+        self.cache.insert(&key, &response.tasks);
+        for t in response.tasks.iter() {
+            self.cache.insert(&format!("task/{}", t.id), t);
+        }
+        self.cache.save();
+
         return Ok(response.tasks);
     }
 
-    pub fn get_task(&self, task_id: &usize) -> Result<Task, reqwest::Error> {
+    /// Tasks by id, the ones missing from the cache being fetched concurrently.
+    /// Tasks which could not be read, deleted or not accessible anymore, are skipped and returned apart.
+    pub fn get_tasks(&self, task_ids: &[usize]) -> Result<FetchedTasks, ApiError> {
+        let results = self.cached_many(task_ids, "task", |ids| self.async_client.block_on(self.async_client.get_tasks(ids)))?;
+
+        let mut fetched = FetchedTasks { tasks: vec![], failures: vec![] };
+        for (id, result) in task_ids.iter().zip(results) {
+            match result {
+                Ok(task) => fetched.tasks.push(task),
+                Err(e) => fetched.failures.push((*id, e)),
+            }
+        }
+
+        return Ok(fetched);
+    }
+
+    pub fn get_task(&self, task_id: &usize) -> Result<Task, ApiError> {
+        return self.cached(&format!("task/{}", task_id), || {
+            let url = format!("tasks/{}.json", task_id);
+            let response: TaskResponse = self.client.get(url.as_str())?;
+            Ok(response.task)
+        });
+    }
+
+    /// People of a project, or of the whole company.
+    pub fn list_people(&self, project_id: Option<&str>) -> Result<Vec<Person>, ApiError> {
+        let url = match project_id {
+            Some(id) => format!("projects/{}/people.json", id),
+            None => "people.json".to_string(),
+        };
+        let response: PeopleResponse = self.client.get(url.as_str())?;
+
+        return Ok(response.people);
+    }
+
+    /// Time entries of everyone since `since_date`, the pages being read concurrently.
+    pub fn all_time_entries(&self, since_date: NaiveDate) -> Result<Vec<TimeEntry>, ApiError> {
+        return self.async_client.block_on(self.async_client.all_time_entries(since_date));
+    }
+
+    pub fn get_project_details(&self, project_id: &str) -> Result<ProjectDetails, ApiError> {
+        let url = format!("projects/{}.json", project_id);
+        let response: ProjectDetailsResponse = self.client.get(url.as_str())?;
+
+        return Ok(response.project);
+    }
+
+    /// Every time entry of a project, by everyone, the pages being read concurrently.
+    pub fn project_logged_time(&self, project_id: &str) -> Result<Vec<LoggedTime>, ApiError> {
+        return self.async_client.block_on(self.async_client.project_logged_time(project_id));
+    }
+
+    /// Minutes of the active time budget of a project, if any.
+    pub fn get_time_budget(&self, project_id: &str) -> Result<Option<i64>, ApiError> {
+        let response: BudgetsResponse = self.client.get_with_params("projects/api/v3/projects/budgets.json", &[
+            ("projectIds", project_id),
+            ("status", "ACTIVE"),
+        ])?;
+
+        return Ok(response.budgets.iter()
+            .find(|b| b.budget_type == "TIME")
+            .map(|b| b.capacity));
+    }
+
+    /// Billability of the project of a target, without tags.
+    pub fn default_billing(&self, target: &EntryTarget) -> Result<Billing, ApiError> {
+        if self.billable_projects.is_empty() {
+            return Ok(Billing::default());
+        }
+
+        let project_id = match target {
+            EntryTarget::Task(task_id) => self.get_task_details(task_id)?.project_id.to_string(),
+            EntryTarget::Project(project_id) => project_id.clone(),
+        };
+
+        return Ok(Billing {
+            is_billable: self.billable_projects.contains(&project_id),
+            tags: vec![],
+        });
+    }
+
+    /// Details of a task, never cached as they change with each time entry.
+    pub fn get_task_details(&self, task_id: &str) -> Result<TaskDetails, ApiError> {
         let url = format!("tasks/{}.json", task_id);
-        let response: TaskResponse = self.client.get(url.as_str())?;
+        let response: TaskDetailsResponse = self.client.get_with_params(url.as_str(), &[("nestSubTasks", "yes")])?;
 
         return Ok(response.task);
     }
 
+    /// Minutes logged on a task by everyone.
+    pub fn get_task_logged_minutes(&self, task_id: &str) -> Result<i64, ApiError> {
+        let logged_time = self.async_client.block_on(self.async_client.task_logged_time(task_id))?;
+
+        return Ok(logged_time.iter().map(|t| t.minutes()).sum());
+    }
+
+    pub fn create_task(&self, tasklist_id: &str, task: &TaskInput) -> Result<CreatedResponse, ApiError> {
+        let body = json!({
+            "todo-item": task
+        });
+
+        let url = format!("tasklists/{}/tasks.json", tasklist_id);
+        let response = self.client.post(url.as_str(), &body)?;
+        self.cache.remove(&format!("tasks/{}", tasklist_id));
+        self.cache.remove("task-index");
+
+        return Ok(response);
+    }
+
+    pub fn complete_task(&self, task_id: &str) -> Result<CreatedResponse, ApiError> {
+        let url = format!("tasks/{}/complete.json", task_id);
+        let response = self.client.put(url.as_str(), &json!({}))?;
+
+        // Read before it is removed : the cached task tells which task list lists it
+        let task_key = format!("task/{}", task_id);
+        if let Some(tasklist_id) = self.cache.get::<Task>(&task_key).and_then(|t| t.todo_list_id) {
+            self.cache.remove(&format!("tasks/{}", tasklist_id));
+        }
+        self.cache.remove(&task_key);
+        self.cache.remove("task-index");
+
+        return Ok(response);
+    }
+
+    pub fn comment_task(&self, task_id: &str, comment: &str) -> Result<CreatedResponse, ApiError> {
+        let body = json!({
+            "comment": {
+                "body": comment
+            }
+        });
+
+        let url = format!("tasks/{}/comments.json", task_id);
+        return self.client.post(url.as_str(), &body);
+    }
+
+    /// Every task of every project, sub tasks included, cached as a whole.
+    /// Task lists and tasks missing from the cache are fetched concurrently.
+    pub fn task_index(&self) -> Result<Vec<Task>, ApiError> {
+        return self.cached("task-index", || {
+            let project_ids: Vec<String> = self.list_project(&None)?.projects.into_iter()
+                .map(|p| p.id)
+                .collect();
+            let tasklist_ids: Vec<String> = self.cached_all(&project_ids, "tasklists", |ids| {
+                self.async_client.block_on(self.async_client.list_tasklists(ids))
+            })?.into_iter().flatten().map(|t| t.id).collect();
+            let tasks_by_tasklist = self.cached_all(&tasklist_ids, "tasks", |ids| {
+                self.async_client.block_on(self.async_client.list_tasks(ids))
+            })?;
+
+            let mut tasks = vec![];
+            // Written with the index itself, once
+            for task in tasks_by_tasklist.into_iter().flatten() {
+                self.cache.insert(&format!("task/{}", task.id), &task);
+
+                let parent = ParentTask { id: task.id.to_string(), name: task.name.clone() };
+                for sub_task in task.sub_tasks.iter() {
+                    tasks.push(Task {
+                        parent_task: sub_task.parent_task.clone().or_else(|| Some(parent.clone())),
+                        ..sub_task.clone()
+                    });
+                }
+
+                tasks.push(Task { sub_tasks: vec![], ..task });
+            }
+
+            Ok(tasks)
+        });
+    }
+
     pub fn last_time_entries(
         &self,
         nb_result: i32,
         start_date: Option<NaiveDate>,
-    ) -> Result<Vec<TimeEntry>, reqwest::Error> {
+    ) -> Result<Vec<TimeEntry>, ApiError> {
         let account = self.get_account()?;
 
         let from_date_opt = start_date.map(|d| d.format("%Y%m%d").to_string());
@@ -88,11 +365,11 @@ impl<'a> TeamWorkService<'a> {
         return Ok(response.time_entries);
     }
 
-    pub fn last_used_tasks(&self) -> Result<Vec<Task>, reqwest::Error> {
+    pub fn last_used_tasks(&self) -> Result<Vec<Task>, ApiError> {
         let time_entries = self.last_time_entries(60, None)?;
 
         let tasks = time_entries.iter()
-            .map(|t| t.task())
+            .filter_map(|t| t.task())
             .fold(vec![], |acc, task|
                 match acc.contains(&task) {
                     true => acc,
@@ -108,94 +385,143 @@ impl<'a> TeamWorkService<'a> {
     }
 
 
-    pub fn get_missing_entries(&self, since_date: NaiveDate, times_off: &Iter<TimeOff>) -> Result<i32, reqwest::Error> {
-        let today = Utc::today().naive_utc();
+    /// Hours missing since `since_date`, today excluded.
+    pub fn get_missing_entries(&self, since_date: NaiveDate, times_off: &Iter<TimeOff>) -> Result<i32, ApiError> {
+        let missing_days = self.get_missing_days(since_date, times_off)?;
+
+        return Ok(missing_days.iter().map(|d| d.hours).sum());
+    }
+
+    pub fn get_missing_days(&self, since_date: NaiveDate, times_off: &Iter<TimeOff>) -> Result<Vec<MissingDay>, ApiError> {
+        let today = Utc::now().date_naive();
 
         if today.le(&since_date) {
-            return Ok(0);
+            return Ok(vec![]);
         }
 
-        let time_entries = self.last_time_entries(500, Some(since_date))?;
-        let existing_time_entries = time_entries.iter();
+        let mut time_entries = self.last_time_entries(500, Some(since_date))?;
+        time_entries.extend(self.queued_entries.iter().cloned());
 
-        let mut missing = 0;
-        let mut d = since_date.clone();
-        while d.lt(&today) {
-            let remaining_workload = get_remaining_workload(d, &existing_time_entries, times_off);
+        return Ok(compute_missing_days(since_date, today, &time_entries.iter(), times_off));
+    }
 
-            if is_working_day(d) {
-                missing += remaining_workload;
-            }
-            d = d.succ();
+    pub fn fill_missing_days(
+        &self,
+        task_id: &str,
+        missing_days: &[MissingDay],
+        description: &str,
+        dry_run: bool,
+    ) -> Result<Vec<SavedDay>, ApiError> {
+        let account = self.get_account()?;
+
+        let mut filled = vec![];
+        for day in missing_days {
+            let response = match dry_run {
+                true => None,
+                false => {
+                    let new_time_entry = TimeEntryInput::new(day.date, day.hours, description, &account.id);
+                    Some(self.post_entry(&EntryTarget::Task(task_id.to_string()), &new_time_entry)?)
+                }
+            };
+
+            filled.push(SavedDay { day: day.clone(), response });
         }
 
-        return Ok(missing);
+        return Ok(filled);
     }
 
-    pub fn save_time(
+    /// Plans `hours` from `start_date` around the time already logged, queued entries included.
+    pub fn plan_time_entries(
         &self,
-        task_id: String,
         start_date: NaiveDate,
         hours: i32,
-        description: String,
-        dry_run: bool,
         times_off: &Iter<TimeOff>,
-    ) -> Result<i32, reqwest::Error> {
+    ) -> Result<Vec<MissingDay>, ApiError> {
+        let mut time_entries = self.last_time_entries(500, Some(start_date))?;
+        debug!("Planning {}h from {} around {} time entries and {} queued ones",
+               hours, start_date, time_entries.len(), self.queued_entries.len());
+        time_entries.extend(self.queued_entries.iter().cloned());
+
+        return Ok(plan_time(start_date, hours, &time_entries.iter(), times_off));
+    }
+
+    /// Spreads `hours` over the working days from `start_date`, around the existing entries and times off,
+    /// and posts an entry per day unless `dry_run`.
+    pub fn save_time(&self, input: &TimeInput, dry_run: bool, times_off: &Iter<TimeOff>) -> Result<Vec<SavedDay>, ApiError> {
         let account = self.get_account()?;
         let account_id = account.id.as_str();
 
-        let time_entries = self.last_time_entries(500, Some(start_date))?;
-        let existing_time_entries = time_entries.iter();
-
-        let mut current_date = start_date.clone();
-        let today = &Utc::today().naive_utc();
-
-        let mut remaining_input_hours = hours.clone();
-
-        println!("Start adding time entries. Remaining hours : {}", remaining_input_hours);
-
-        while current_date.le(today) && remaining_input_hours > 0 {
-            let remaining_workload = get_remaining_workload(current_date, &existing_time_entries, times_off);
-
-            println!("{} - {} / {} : {}",
-                     current_date.format("%Y%m%d"),
-                     remaining_workload,
-                     remaining_input_hours,
-                     description);
-            if !dry_run && remaining_workload == 0 {
-                println!("\t 💤 (no work)")
-            } else if !dry_run {
-                let new_time_entry = TimeEntryInput {
-                    date: current_date.format("%Y%m%d").to_string(),
-                    time: "08:00".to_string(),
-                    hours: remaining_workload.to_string(),
-                    description: description.clone(),
-                    minutes: "0".to_string(),
-                    person_id: account_id.to_string(),
-                };
-
-                let response = self.save_time_entry(task_id.clone(), &new_time_entry)?;
-                let id = response.id.unwrap_or_else(|| "unknown".to_string());
-                match response.status.as_str() {
-                    "OK" => println!("\t ✔️ (#id : {})", id),
-                    _ => {
-                        println!("\t ❓ {} (#id : {})", response.status, id);
-                    }
-                }
-            }
+        let planned_days = self.plan_time_entries(input.start_date, input.hours, times_off)?;
 
-            remaining_input_hours -= remaining_workload;
+        let mut saved_days = vec![];
+        for day in planned_days {
+            let response = if dry_run {
+                debug!("{} not saved : dry run", day.date);
+                None
+            } else if day.hours == 0 {
+                debug!("{} skipped : nothing left to log", day.date);
+                None
+            } else {
+                let new_time_entry = TimeEntryInput::new(day.date, day.hours, &input.description, account_id)
+                    .with_billing(&input.billing);
 
-            current_date = current_date.succ();
-            while !is_working_day(current_date) {
-                current_date = current_date.succ();
-            }
+                Some(self.save_entry(&input.target, &new_time_entry)?)
+            };
+
+            saved_days.push(SavedDay { day, response });
         }
 
-        return Ok(hours);
+        return Ok(saved_days);
     }
 
-    pub fn save_time_entry(&self, task_id: String, time_entry: &TimeEntryInput) -> Result<TimeEntryCreatedResponse, reqwest::Error> {
+    /// Logs the entries of the templates occurring during the 7 days starting at `week_start`.
+    /// `templates` task must be resolved ids.
+    pub fn apply_templates(
+        &self,
+        templates: &[TimeEntryTemplate],
+        week_start: NaiveDate,
+        dry_run: bool,
+        times_off: &Iter<TimeOff>,
+    ) -> Result<Vec<(PlannedEntry, Option<TimeEntryCreatedResponse>)>, ApiError> {
+        let account = self.get_account()?;
+
+        let mut time_entries = self.last_time_entries(500, Some(week_start))?;
+        time_entries.extend(self.queued_entries.iter().cloned());
+        let planned_entries = plan_templates(templates, week_start, &time_entries.iter(), times_off);
+
+        let mut applied = vec![];
+        for entry in planned_entries {
+            let response = match dry_run {
+                true => None,
+                false => {
+                    let start = entry.date.and_hms_opt(8, 0, 0).unwrap();
+                    let new_time_entry = TimeEntryInput::for_duration(start, entry.minutes, &entry.description, &account.id);
+                    Some(self.post_entry(&EntryTarget::Task(entry.task_id.clone()), &new_time_entry)?)
+                }
+            };
+
+            applied.push((entry, response));
+        }
+
+        return Ok(applied);
+    }
+
+    /// Posts `time_entry` on `target`, billable when its project is billable by default.
+    pub fn post_entry(&self, target: &EntryTarget, time_entry: &TimeEntryInput) -> Result<TimeEntryCreatedResponse, ApiError> {
+        let billing = self.default_billing(target)?;
+
+        return self.save_entry(target, &time_entry.with_billing(&billing));
+    }
+
+    /// Posts the `minutes` of a stopped `timer`, from its start.
+    pub fn log_timer(&self, timer: &Timer, minutes: i64, description: &str) -> Result<TimeEntryCreatedResponse, ApiError> {
+        let account = self.get_account()?;
+        let time_entry = TimeEntryInput::for_duration(timer.started_at.naive_local(), minutes, description, &account.id);
+
+        return self.post_entry(&EntryTarget::Task(timer.task_id.clone()), &time_entry);
+    }
+
+    pub fn save_entry(&self, target: &EntryTarget, time_entry: &TimeEntryInput) -> Result<TimeEntryCreatedResponse, ApiError> {
         let value = serde_json::to_value(time_entry)
             .expect("Could not parse time entry to json value");
 
@@ -203,7 +529,10 @@ impl<'a> TeamWorkService<'a> {
             "time-entry": value
         });
 
-        let path = format!("/tasks/{}/time_entries.json", task_id);
+        let path = match target {
+            EntryTarget::Task(task_id) => format!("/tasks/{}/time_entries.json", task_id),
+            EntryTarget::Project(project_id) => format!("/projects/{}/time_entries.json", project_id),
+        };
         return self.client.post(path.as_str(), &body);
     }
 
@@ -212,10 +541,112 @@ impl<'a> TeamWorkService<'a> {
 // create a time entry for a task https://developer.teamwork.com/projects/time-tracking/create-a-time-entry-for-a-task
 }
 
-fn is_working_day(d: NaiveDate) -> bool {
+/// Days on which `hours` are logged from `start_date`, each one topped up to its remaining workload.
+pub fn plan_time(
+    start_date: NaiveDate,
+    hours: i32,
+    existing_time_entries: &Iter<TimeEntry>,
+    times_off: &Iter<TimeOff>,
+) -> Vec<MissingDay> {
+    let today = Utc::now().date_naive();
+
+    let mut planned_days = vec![];
+    let mut current_date = start_date;
+    let mut remaining_input_hours = hours;
+
+    while current_date.le(&today) && remaining_input_hours > 0 {
+        let day_workload = get_remaining_workload(current_date, existing_time_entries, times_off);
+        let remaining_workload = day_workload.min(remaining_input_hours);
+        debug!("{} : {}h left to log, {}h planned, {}h remaining to place",
+               current_date, day_workload, remaining_workload, remaining_input_hours - remaining_workload);
+
+        planned_days.push(MissingDay { date: current_date, hours: remaining_workload });
+        remaining_input_hours -= remaining_workload;
+
+        current_date = current_date.succ_opt().unwrap();
+        while !is_working_day(current_date) {
+            current_date = current_date.succ_opt().unwrap();
+        }
+    }
+
+    if remaining_input_hours > 0 {
+        debug!("{}h not placed : no working day left until {}", remaining_input_hours, today);
+    }
+
+    return planned_days;
+}
+
+/// Working days from `since_date` until `until_date` (excluded) which are not fully logged,
+/// with the hours left once the time entries and times off are subtracted.
+pub fn compute_missing_days(
+    since_date: NaiveDate,
+    until_date: NaiveDate,
+    existing_time_entries: &Iter<TimeEntry>,
+    times_off: &Iter<TimeOff>,
+) -> Vec<MissingDay> {
+    let mut missing_days = vec![];
+    let mut d = since_date;
+    while d.lt(&until_date) {
+        if is_working_day(d) {
+            let remaining_workload = get_remaining_workload(d, existing_time_entries, times_off);
+            if remaining_workload > 0 {
+                missing_days.push(MissingDay { date: d, hours: remaining_workload });
+            }
+        }
+        d = d.succ_opt().unwrap();
+    }
+
+    return missing_days;
+}
+
+fn plan_templates(
+    templates: &[TimeEntryTemplate],
+    week_start: NaiveDate,
+    existing_time_entries: &Iter<TimeEntry>,
+    times_off: &Iter<TimeOff>,
+) -> Vec<PlannedEntry> {
+    let mut planned_entries = vec![];
+
+    for date in (0..7).map(|i| week_start + Duration::days(i)) {
+        if get_remaining_workload(date, existing_time_entries, times_off) == 0 {
+            continue;
+        }
+
+        for template in templates.iter().filter(|t| t.occurs_on(date)) {
+            let already_logged = existing_time_entries.clone()
+                .any(|e| e.date.date_naive() == date
+                    && e.todo_item_id == template.task
+                    && e.description == template.description);
+
+            if !already_logged {
+                planned_entries.push(PlannedEntry {
+                    template: template.name.clone(),
+                    task_id: template.task.clone(),
+                    date,
+                    minutes: template.minutes,
+                    description: template.description.clone(),
+                });
+            }
+        }
+    }
+
+    return planned_entries;
+}
+
+/// Monday to friday.
+pub fn is_working_day(d: NaiveDate) -> bool {
     return d.weekday() != Weekday::Sat && d.weekday() != Weekday::Sun;
 }
 
+/// Hours expected on a day : a working day minus its time off.
+pub fn expected_workload(date: NaiveDate, times_off: &Iter<TimeOff>) -> i32 {
+    if !is_working_day(date) {
+        return 0;
+    }
+
+    return get_remaining_workload(date, &[].iter(), times_off);
+}
+
 fn get_remaining_workload(
     date: NaiveDate,
     existing_time_entries: &Iter<TimeEntry>,
@@ -223,11 +654,12 @@ fn get_remaining_workload(
 ) -> i32 {
     let existings = existing_time_entries
         .clone()
-        .filter(|t| t.date.date().naive_utc() == date);
+        .filter(|t| t.date.date_naive() == date);
 
-    let mut remaining_workload = WORKING_DAY_DURATION;
+    // Counted in minutes, for entries of a stopped timer not to be lost in the rounding
+    let mut remaining_workload = WORKING_DAY_DURATION * 60;
     for e in existings {
-        remaining_workload -= e.hours();
+        remaining_workload -= e.minutes();
     }
 
     let tos = times_off
@@ -235,24 +667,56 @@ fn get_remaining_workload(
         .filter(|t| t.date == date.format("%Y-%m-%d").to_string());
 
     for t in tos {
-        remaining_workload -= t.hours;
+        remaining_workload -= t.hours * 60;
     }
 
     if remaining_workload < 0 {
         remaining_workload = 0;
     }
 
-    return remaining_workload;
+    return minutes_to_hours(remaining_workload);
 }
 
-#[derive(Debug, Deserialize)]
+/// A working day which is not fully logged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingDay {
+    pub date: NaiveDate,
+    pub hours: i32,
+}
+
+/// Tasks read by `get_tasks`.
+#[derive(Debug)]
+pub struct FetchedTasks {
+    pub tasks: Vec<Task>,
+    /// Ids of the tasks which could not be read, with the reason.
+    pub failures: Vec<(usize, ApiError)>,
+}
+
+/// A day planned by `save_time` or `fill_missing_days`, with the response of Teamwork when an entry was posted.
+#[derive(Debug)]
+pub struct SavedDay {
+    pub day: MissingDay,
+    /// `None` on a dry run or when nothing was left to log on that day.
+    pub response: Option<TimeEntryCreatedResponse>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedEntry {
+    pub template: String,
+    pub task_id: String,
+    pub date: NaiveDate,
+    pub minutes: i64,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectsResponse {
     #[serde(alias = "STATUS")]
     pub status: String,
     pub projects: Vec<Project>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Project {
     pub id: String,
     pub name: String,
@@ -266,7 +730,7 @@ pub struct AccountResponse {
     pub account: Account,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Account {
     pub id: String,
 }
@@ -278,7 +742,7 @@ pub struct TasklistsResponse {
     pub tasklists: Vec<TaskList>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TaskList {
     pub id: String,
     pub name: String,
@@ -295,12 +759,14 @@ pub struct TimeEntriesResponse {
     pub time_entries: Vec<TimeEntry>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct TimeEntry {
     pub id: String,
     pub description: String,
     pub date: DateTime<Utc>,
     pub hours: String,
+    #[serde(default)]
+    pub minutes: String,
     #[serde(alias = "project-id")]
     pub project_id: String,
     #[serde(alias = "project-name")]
@@ -313,27 +779,85 @@ pub struct TimeEntry {
     pub todo_item_id: String,
     #[serde(alias = "todo-item-name")]
     pub todo_item_name: String,
+    #[serde(default, alias = "person-id")]
+    pub person_id: String,
+    /// "1" when billable
+    #[serde(default, alias = "isbillable")]
+    pub is_billable: String,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Tag {
+    pub name: String,
 }
 
 impl TimeEntry {
-    pub fn hours(&self) -> i32 {
-        return self.hours.parse::<i32>().unwrap();
+    pub fn is_billable(&self) -> bool {
+        return self.is_billable == "1";
+    }
+
+    pub fn tag_names(&self) -> Vec<&str> {
+        return self.tags.iter().map(|t| t.name.as_str()).collect();
     }
 
-    pub fn task(&self) -> Task {
-        let id = self.todo_item_id.clone();
+    /// Whether the entry has the billability `billable`, when given, and all the `tags`.
+    pub fn matches(&self, billable: Option<bool>, tags: &[String]) -> bool {
+        let tag_names = self.tag_names();
+
+        return billable.map(|b| b == self.is_billable()).unwrap_or(true)
+            && tags.iter().all(|t| tag_names.contains(&t.as_str()));
+    }
+
+    /// Time logged, in minutes. A duration which is not made of numbers counts as nothing.
+    pub fn minutes(&self) -> i32 {
+        let parse = |value: &str| match value.is_empty() {
+            true => Some(0),
+            false => value.parse::<i32>().ok(),
+        };
+        let minutes = parse(&self.hours)
+            .and_then(|h| h.checked_mul(60))
+            .and_then(|h| h.checked_add(parse(&self.minutes)?));
+
+        return minutes.unwrap_or_else(|| {
+            warn!("Time entry #{} has an invalid duration {}h{}, counted as nothing", self.id, self.hours, self.minutes);
+            0
+        });
+    }
+
+    /// The task of the entry, `None` for an entry logged on a project.
+    pub fn task(&self) -> Option<Task> {
+        let id = self.todo_item_id.parse().ok().filter(|id| *id != 0)?;
         let name = self.todo_item_name.clone();
-        return Task {
-            id: id.parse().unwrap(),
+        return Some(Task {
+            id,
             name, sub_tasks: vec![],
-            todo_list_name: "".to_string(),
-            project_name: "".to_string(),
+            todo_list_name: self.todo_list_name.clone(),
+            todo_list_id: self.todo_list_id.parse().ok(),
+            project_name: self.project_name.clone(),
             parent_task: None,
+        });
+    }
+}
+
+#[cfg(test)]
+impl TimeEntry {
+    /// Entry of `hours` logged at 8:00 on `date` on the task `task`, the other fields being empty, for the tests.
+    pub fn logged_on(date: NaiveDate, hours: i32, task: &str) -> TimeEntry {
+        return TimeEntry {
+            id: "1".to_string(),
+            date: DateTime::from_naive_utc_and_offset(date.and_hms_opt(8, 0, 0).unwrap(), Utc),
+            hours: hours.to_string(),
+            project_id: "1".to_string(),
+            project_name: "Project".to_string(),
+            todo_item_name: task.to_string(),
+            ..Default::default()
         };
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TimeEntryInput {
     pub description: String,
     #[serde(rename = "person-id")]
@@ -342,6 +866,80 @@ pub struct TimeEntryInput {
     pub time: String,
     pub hours: String,
     pub minutes: String,
+    #[serde(default, rename = "isbillable")]
+    pub is_billable: bool,
+    /// Comma separated tag names
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tags: String,
+}
+
+/// What time is logged on : a task, or a project for the clients who don't use tasks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryTarget {
+    Task(String),
+    Project(String),
+}
+
+impl fmt::Display for EntryTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            EntryTarget::Task(id) => write!(f, "task #{}", id),
+            EntryTarget::Project(id) => write!(f, "project #{}", id),
+        };
+    }
+}
+
+/// Hours to spread over the working days from `start_date`, logged on `target`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeInput {
+    pub target: EntryTarget,
+    pub start_date: NaiveDate,
+    pub hours: i32,
+    pub description: String,
+    pub billing: Billing,
+}
+
+/// Billability and tags of the time entries to create.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Billing {
+    pub is_billable: bool,
+    pub tags: Vec<String>,
+}
+
+impl TimeEntryInput {
+    pub fn new(date: NaiveDate, hours: i32, description: &str, person_id: &str) -> TimeEntryInput {
+        return TimeEntryInput {
+            date: date.format("%Y%m%d").to_string(),
+            time: "08:00".to_string(),
+            hours: hours.to_string(),
+            description: description.to_string(),
+            minutes: "0".to_string(),
+            person_id: person_id.to_string(),
+            is_billable: false,
+            tags: String::new(),
+        };
+    }
+
+    pub fn for_duration(start: NaiveDateTime, minutes: i64, description: &str, person_id: &str) -> TimeEntryInput {
+        return TimeEntryInput {
+            date: start.format("%Y%m%d").to_string(),
+            time: start.format("%H:%M").to_string(),
+            hours: (minutes / 60).to_string(),
+            description: description.to_string(),
+            minutes: (minutes % 60).to_string(),
+            person_id: person_id.to_string(),
+            is_billable: false,
+            tags: String::new(),
+        };
+    }
+
+    pub fn with_billing(&self, billing: &Billing) -> TimeEntryInput {
+        return TimeEntryInput {
+            is_billable: billing.is_billable,
+            tags: billing.tags.join(","),
+            ..self.clone()
+        };
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -352,6 +950,184 @@ pub struct TimeEntryCreatedResponse {
     pub status: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CreatedResponse {
+    #[serde(alias = "commentId")]
+    pub id: Option<String>,
+    #[serde(alias = "STATUS")]
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskInput {
+    pub content: String,
+    pub description: String,
+    #[serde(rename = "estimated-minutes")]
+    pub estimated_minutes: i64,
+    #[serde(rename = "responsible-party-id", skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskDetailsResponse {
+    #[serde(alias = "STATUS")]
+    pub status: String,
+    #[serde(alias = "todo-item")]
+    pub task: TaskDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskDetails {
+    pub id: usize,
+    #[serde(alias = "content")]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(alias = "project-name")]
+    pub project_name: String,
+    #[serde(alias = "todo-list-name")]
+    pub todo_list_name: String,
+    #[serde(default, alias = "project-id")]
+    pub project_id: usize,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default, alias = "estimated-minutes")]
+    pub estimated_minutes: i64,
+    /// Names separated by `|`
+    #[serde(default, alias = "responsible-party-names")]
+    pub assignees: Option<String>,
+    #[serde(default, alias = "subTasks")]
+    pub sub_tasks: Vec<Task>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskTimeEntriesResponse {
+    #[serde(alias = "STATUS")]
+    pub status: String,
+    #[serde(alias = "time-entries")]
+    pub time_entries: Vec<LoggedTime>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoggedTime {
+    pub hours: String,
+    #[serde(default)]
+    pub minutes: String,
+    #[serde(default, alias = "person-id")]
+    pub person_id: String,
+    /// "1" when billable
+    #[serde(default, alias = "isbillable")]
+    pub is_billable: String,
+}
+
+impl LoggedTime {
+    pub fn minutes(&self) -> i64 {
+        return self.hours.parse::<i64>().unwrap_or(0) * 60 + self.minutes.parse::<i64>().unwrap_or(0);
+    }
+}
+
+/// Minutes logged on a project.
+#[derive(Debug, Default, PartialEq)]
+pub struct ProjectTime {
+    pub total: i64,
+    pub billable: i64,
+    pub non_billable: i64,
+    /// Logged by the current account
+    pub mine: i64,
+}
+
+pub fn sum_project_time(logged_time: &[LoggedTime], account_id: &str) -> ProjectTime {
+    let mut time = ProjectTime::default();
+
+    for t in logged_time {
+        let minutes = t.minutes();
+        time.total += minutes;
+        match t.is_billable.as_str() {
+            "1" => time.billable += minutes,
+            _ => time.non_billable += minutes,
+        }
+        if t.person_id == account_id {
+            time.mine += minutes;
+        }
+    }
+
+    return time;
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeopleResponse {
+    #[serde(alias = "STATUS")]
+    pub status: String,
+    pub people: Vec<Person>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Person {
+    pub id: String,
+    #[serde(alias = "first-name")]
+    pub first_name: String,
+    #[serde(alias = "last-name")]
+    pub last_name: String,
+    #[serde(default, alias = "email-address")]
+    pub email: String,
+}
+
+impl Person {
+    pub fn name(&self) -> String {
+        return format!("{} {}", self.first_name, self.last_name);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectDetailsResponse {
+    #[serde(alias = "STATUS")]
+    pub status: String,
+    pub project: ProjectDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectDetails {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub company: Option<ProjectCompany>,
+    #[serde(default)]
+    pub status: String,
+    /// Formatted as `%Y%m%d`
+    #[serde(default, alias = "startDate")]
+    pub start_date: String,
+    #[serde(default, alias = "endDate")]
+    pub end_date: String,
+    #[serde(default)]
+    pub owner: Option<ProjectOwner>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectCompany {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectOwner {
+    #[serde(alias = "fullName")]
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BudgetsResponse {
+    #[serde(default)]
+    pub budgets: Vec<Budget>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Budget {
+    #[serde(alias = "type")]
+    pub budget_type: String,
+    /// Minutes for a time budget
+    pub capacity: i64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TasksResponse {
     #[serde(alias = "STATUS")]
@@ -368,13 +1144,16 @@ pub struct TaskResponse {
     pub task: Task,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct Task {
     pub id: usize,
     #[serde(alias = "project-name")]
     pub project_name: String,
     #[serde(alias = "todo-list-name")]
     pub todo_list_name: String,
+    /// Missing from the tasks cached by older versions.
+    #[serde(default, alias = "todo-list-id")]
+    pub todo_list_id: Option<usize>,
     #[serde(alias = "parent-task")]
     pub parent_task: Option<ParentTask>,
     #[serde(alias = "content")]
@@ -383,7 +1162,28 @@ pub struct Task {
     pub sub_tasks: Vec<Task>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Clone)]
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match &self.parent_task {
+            Some(p) => write!(f, "{} > {} > {} > {}", self.project_name, self.todo_list_name, p.name, self.name),
+            None => write!(f, "{} > {} > {}", self.project_name, self.todo_list_name, self.name),
+        };
+    }
+}
+
+impl fmt::Display for Project {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.name);
+    }
+}
+
+impl fmt::Display for TaskList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} ({} tasks)", self.name, self.uncompleted_count);
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct ParentTask {
     pub id: String,
     #[serde(alias = "content")]
@@ -391,70 +1191,208 @@ pub struct ParentTask {
 }
 
 #[derive(Clone)]
-struct HttpClient<'a> {
-    company_id: &'a str,
-    token: &'a str,
+struct HttpClient {
+    transport: Arc<dyn Transport>,
 }
 
-impl<'a> HttpClient<'a> {
-    fn new<'b>(config: &'b TeamWorkConfig) -> HttpClient<'b> {
-        return HttpClient {
-            company_id: &config.company_id,
-            token: &config.token,
-        };
+impl HttpClient {
+    fn post<O, T>(&self, path: &str, body: &T) -> Result<O, ApiError>
+        where O: DeserializeOwned,
+              T: Serialize + ?Sized
+    {
+        let body = serde_json::to_value(body)
+            .expect("Could not serialize to json");
+
+        return self.transport.send(&ApiRequest::new("POST", path).with_body(body))?.decode();
     }
 
-    fn post<O, T: ?Sized>(&self, path: &str, body: &T) -> Result<O, reqwest::Error>
+    fn put<O, T>(&self, path: &str, body: &T) -> Result<O, ApiError>
         where O: DeserializeOwned,
-              T: Serialize
+              T: Serialize + ?Sized
     {
-        let url = format!("https://{}.eu.teamwork.com/{}", self.company_id, path);
-
-        let body_as_string = serde_json::to_string(body)
+        let body = serde_json::to_value(body)
             .expect("Could not serialize to json");
 
-        let client = reqwest::Client::new();
-        let no_password: Option<String> = None;
-        let body: O = client.post(url.as_str())
-            .basic_auth(self.token, no_password)
-            .body(body_as_string)
-            .send()?
-            .json()?;
+        return self.transport.send(&ApiRequest::new("PUT", path).with_body(body))?.decode();
+    }
 
-        return Ok(body);
+    fn get<O>(&self, path: &str) -> Result<O, ApiError> where O: DeserializeOwned {
+        return self.transport.send(&ApiRequest::new("GET", path))?.decode();
     }
 
-    fn get<O>(&self, path: &str) -> Result<O, reqwest::Error> where O: DeserializeOwned {
-        let url = format!("https://{}.eu.teamwork.com/{}", self.company_id, path);
+    fn get_with_params<K, V, O>(&self, path: &str, query_params: &[(K, V)]) -> Result<O, ApiError>
+        where K: AsRef<str>,
+              V: AsRef<str>,
+              O: DeserializeOwned {
+        return self.transport.send(&ApiRequest::new("GET", path).with_query(query_params))?.decode();
+    }
+}
 
-        let client = reqwest::Client::new();
-        let no_password: Option<String> = None;
-        let body: O = client.get(url.as_str())
-            .basic_auth(self.token, no_password)
-            .send()?
-            .json()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_days_skip_week_ends_and_times_off() {
+        let since = NaiveDate::from_ymd_opt(2020, 1, 23).unwrap();
+        let until = NaiveDate::from_ymd_opt(2020, 1, 28).unwrap();
+        let time_entries: Vec<TimeEntry> = vec![];
+        let times_off = [
+            TimeOff {
+                date: "2020-01-24".to_string(),
+                hours: 4,
+            }
+        ];
+
+        let missing_days = compute_missing_days(since, until, &time_entries.iter(), &times_off.iter());
 
-        return Ok(body);
+        assert_eq!(missing_days, vec![
+            MissingDay { date: NaiveDate::from_ymd_opt(2020, 1, 23).unwrap(), hours: 8 },
+            MissingDay { date: NaiveDate::from_ymd_opt(2020, 1, 24).unwrap(), hours: 4 },
+            MissingDay { date: NaiveDate::from_ymd_opt(2020, 1, 27).unwrap(), hours: 8 },
+        ]);
     }
 
-    fn get_with_params<I, K, V, O>(&self, path: &str, query_params: I) -> Result<O, reqwest::Error>
-        where I: IntoIterator,
-              I::Item: Borrow<(K, V)>,
-              K: AsRef<str>,
-              V: AsRef<str>,
-              O: DeserializeOwned {
-        let url = format!("https://{}.eu.teamwork.com/{}", self.company_id, path);
+    #[test]
+    fn test_logged_minutes_are_counted() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2020, 1, d).unwrap();
+        let with_minutes = |d: u32, hours: i32, minutes: &str| TimeEntry { minutes: minutes.to_string(), ..TimeEntry::logged_on(day(d), hours, "Support") };
+        let time_entries = [
+            with_minutes(20, 0, "45"),
+            with_minutes(21, 7, "30"),
+            with_minutes(21, 0, "30"),
+            TimeEntry { hours: "not a number".to_string(), ..TimeEntry::logged_on(day(22), 0, "Support") },
+            TimeEntry { hours: "99999999".to_string(), ..TimeEntry::logged_on(day(23), 0, "Support") },
+        ];
+
+        assert_eq!(time_entries[0].minutes(), 45);
+        assert_eq!(time_entries[3].minutes(), 0);
+        assert_eq!(time_entries[4].minutes(), 0);
+
+        let missing_days = compute_missing_days(day(20), day(23), &time_entries.iter(), &[].iter());
+
+        assert_eq!(missing_days, vec![
+            MissingDay { date: day(20), hours: 7 },
+            MissingDay { date: day(22), hours: 8 },
+        ]);
+    }
+
+    #[test]
+    fn test_templates_skip_days_off_and_logged_entries() {
+        let template = TimeEntryTemplate {
+            name: "stand-up".to_string(),
+            task: "42".to_string(),
+            minutes: 15,
+            description: "Stand-up".to_string(),
+            weekdays: vec![],
+            every_weeks: 1,
+            since: None,
+        };
+        let time_entries = [
+            TimeEntry {
+                description: "Stand-up".to_string(),
+                todo_item_id: "42".to_string(),
+                ..TimeEntry::logged_on(NaiveDate::from_ymd_opt(2020, 1, 21).unwrap(), 0, "Stand-up")
+            }
+        ];
+        let times_off = [
+            TimeOff {
+                date: "2020-01-22".to_string(),
+                hours: 8,
+            }
+        ];
 
-        let with_params = Url::parse_with_params(&url, query_params)
-            .expect("Could not parse url");
+        let week_start = NaiveDate::from_ymd_opt(2020, 1, 20).unwrap();
+        let planned = plan_templates(&[template], week_start, &time_entries.iter(), &times_off.iter());
 
-        let client = reqwest::Client::new();
-        let no_password: Option<String> = None;
-        let body: O = client.get(with_params.as_str())
-            .basic_auth(self.token, no_password)
-            .send()?
-            .json()?;
+        let dates: Vec<NaiveDate> = planned.iter().map(|p| p.date).collect();
+        assert_eq!(dates, vec![
+            NaiveDate::from_ymd_opt(2020, 1, 20).unwrap(),
+            NaiveDate::from_ymd_opt(2020, 1, 23).unwrap(),
+            NaiveDate::from_ymd_opt(2020, 1, 24).unwrap(),
+        ]);
+    }
+
+    #[test]
+    fn test_task_details_are_read_from_teamwork() {
+        let json = r#"{
+            "STATUS": "OK",
+            "todo-item": {
+                "id": 42,
+                "content": "Support",
+                "project-name": "Project",
+                "todo-list-name": "Run",
+                "completed": false,
+                "estimated-minutes": 90,
+                "responsible-party-names": "Ada L.|Alan T."
+            }
+        }"#;
+
+        let response: TaskDetailsResponse = serde_json::from_str(json).unwrap();
+
+        assert_eq!(response.task.id, 42);
+        assert_eq!(response.task.estimated_minutes, 90);
+        assert_eq!(response.task.assignees, Some("Ada L.|Alan T.".to_string()));
+        assert!(response.task.sub_tasks.is_empty());
+        let logged = LoggedTime {
+            hours: "1".to_string(),
+            minutes: "15".to_string(),
+            person_id: String::new(),
+            is_billable: String::new(),
+        };
+        assert_eq!(logged.minutes(), 75);
+    }
+
+    #[test]
+    fn test_project_time_is_summed_by_billability_and_person() {
+        let logged = |hours: &str, minutes: &str, person_id: &str, is_billable: &str| LoggedTime {
+            hours: hours.to_string(),
+            minutes: minutes.to_string(),
+            person_id: person_id.to_string(),
+            is_billable: is_billable.to_string(),
+        };
+        let logged_time = [
+            logged("1", "15", "7", "1"),
+            logged("2", "0", "8", "0"),
+            logged("0", "30", "7", "0"),
+        ];
+
+        assert_eq!(sum_project_time(&logged_time, "7"), ProjectTime {
+            total: 225,
+            billable: 75,
+            non_billable: 150,
+            mine: 105,
+        });
+    }
 
-        return Ok(body);
+    #[test]
+    fn test_time_entries_are_filtered_by_billability_and_tags() {
+        let json = r#"{
+            "id": "1",
+            "description": "Support",
+            "date": "2020-01-21T08:00:00Z",
+            "hours": "2",
+            "project-id": "1",
+            "project-name": "Project",
+            "todo-list-id": "2",
+            "todo-list-name": "Run",
+            "todo-item-id": "42",
+            "todo-item-name": "Support",
+            "isbillable": "1",
+            "tags": [{ "id": "3", "name": "on-site", "color": "red" }]
+        }"#;
+        let entry: TimeEntry = serde_json::from_str(json).unwrap();
+
+        assert!(entry.matches(None, &[]));
+        assert!(entry.matches(Some(true), &["on-site".to_string()]));
+        assert!(!entry.matches(Some(false), &[]));
+        assert!(!entry.matches(None, &["remote".to_string()]));
+
+        let billing = Billing { is_billable: true, tags: vec!["on-site".to_string(), "urgent".to_string()] };
+        let input = TimeEntryInput::new(NaiveDate::from_ymd_opt(2020, 1, 21).unwrap(), 2, "Support", "7")
+            .with_billing(&billing);
+        let value = serde_json::to_value(&input).unwrap();
+        assert_eq!(value["isbillable"], json!(true));
+        assert_eq!(value["tags"], json!("on-site,urgent"));
     }
 }
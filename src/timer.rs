@@ -0,0 +1,117 @@
+use std::error::Error;
+use std::fs;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+/// A timer running (or paused) on a task, persisted in ~/.teamwork-timer so it survives reboots.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Timer {
+    pub task_id: String,
+    pub task_name: String,
+    pub started_at: DateTime<Local>,
+    /// Start of the current running period, `None` while paused.
+    pub resumed_at: Option<DateTime<Local>>,
+    /// Time accumulated by the previous running periods.
+    pub elapsed_seconds: i64,
+}
+
+impl Timer {
+    pub fn start(task_id: &str, task_name: &str, now: DateTime<Local>) -> Timer {
+        return Timer {
+            task_id: task_id.to_string(),
+            task_name: task_name.to_string(),
+            started_at: now,
+            resumed_at: Some(now),
+            elapsed_seconds: 0,
+        };
+    }
+
+    pub fn is_running(&self) -> bool {
+        return self.resumed_at.is_some();
+    }
+
+    pub fn elapsed(&self, now: DateTime<Local>) -> Duration {
+        let running = self.resumed_at
+            .map(|r| now.signed_duration_since(r))
+            .unwrap_or_else(Duration::zero);
+
+        return Duration::seconds(self.elapsed_seconds) + running;
+    }
+
+    pub fn pause(&self, now: DateTime<Local>) -> Timer {
+        return Timer {
+            resumed_at: None,
+            elapsed_seconds: self.elapsed(now).num_seconds(),
+            ..self.clone()
+        };
+    }
+
+    pub fn resume(&self, now: DateTime<Local>) -> Timer {
+        return Timer {
+            resumed_at: Some(now),
+            ..self.clone()
+        };
+    }
+}
+
+pub fn get_timer() -> Result<Option<Timer>, Box<dyn Error>> {
+    let path = get_timer_file()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file_content = fs::read_to_string(path)?;
+    let timer: Timer = serde_json::from_str(&file_content)?;
+
+    return Ok(Some(timer));
+}
+
+pub fn save_timer(timer: &Timer) -> IoResult<()> {
+    let json = serde_json::to_string_pretty(timer)
+        .expect("Could not serialize timer");
+
+    return fs::write(get_timer_file()?, json);
+}
+
+pub fn clear_timer() -> IoResult<()> {
+    let path = get_timer_file()?;
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    return Ok(());
+}
+
+fn get_timer_file() -> IoResult<PathBuf> {
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| IoError::new(ErrorKind::NotFound, "Could not get your home dir"))?;
+
+    return Ok(home_dir.join(".teamwork-timer"));
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    return format!("{}h{:02}", minutes / 60, minutes % 60);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_paused_time_is_not_counted() {
+        let start = Local.with_ymd_and_hms(2020, 1, 23, 9, 0, 0).unwrap();
+
+        let timer = Timer::start("42", "Support", start)
+            .pause(start + Duration::minutes(45))
+            .resume(start + Duration::minutes(60));
+
+        assert!(timer.is_running());
+        assert_eq!(timer.elapsed(start + Duration::minutes(90)), Duration::minutes(75));
+        assert_eq!(format_duration(timer.elapsed(start + Duration::minutes(90))), "1h15");
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use futures::{future, Future};
+use log::{debug, log_enabled, trace, warn, Level};
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::logging::redact;
+use crate::teamwork_config::TeamWorkConfig;
+
+/// File of recorded exchanges to answer the requests from, instead of Teamwork.
+pub const REPLAY_ENV: &str = "TEAMWORK_REPLAY";
+/// File to record the exchanges with Teamwork into.
+pub const RECORD_ENV: &str = "TEAMWORK_RECORD";
+
+pub type SendFuture = Box<dyn Future<Item = ApiResponse, Error = ApiError> + Send>;
+
+/// A request to the Teamwork API, its path being relative to the url of the company.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ApiRequest {
+    pub method: String,
+    pub path: String,
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+impl ApiRequest {
+    pub fn new(method: &str, path: &str) -> ApiRequest {
+        return ApiRequest {
+            method: method.to_string(),
+            path: path.trim_start_matches('/').to_string(),
+            query: BTreeMap::new(),
+            body: None,
+        };
+    }
+
+    pub fn with_query<K: AsRef<str>, V: AsRef<str>>(self, params: &[(K, V)]) -> ApiRequest {
+        let mut query = self.query;
+        query.extend(params.iter().map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string())));
+
+        return ApiRequest { query, ..self };
+    }
+
+    pub fn with_body(self, body: serde_json::Value) -> ApiRequest {
+        return ApiRequest { body: Some(body), ..self };
+    }
+}
+
+/// A response of the Teamwork API. Bodies which are not JSON are kept as a JSON string.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    /// Header names are lower case
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: serde_json::Value,
+}
+
+impl ApiResponse {
+    /// Body of a successful response.
+    pub fn decode<O: DeserializeOwned>(self) -> Result<O, ApiError> {
+        if !(200..300).contains(&self.status) {
+            return Err(ApiError::Status(self.status, self.body));
+        }
+
+        return Ok(serde_json::from_value(self.body)?);
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        return self.headers.get(&name.to_lowercase()).map(|v| v.as_str());
+    }
+
+    fn read(status: StatusCode, headers: &HeaderMap, text: String) -> ApiResponse {
+        return ApiResponse {
+            status: status.as_u16(),
+            headers: headers.iter()
+                .filter_map(|(k, v)| v.to_str().ok().map(|v| (k.as_str().to_lowercase(), v.to_string())))
+                .collect(),
+            body: serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text)),
+        };
+    }
+}
+
+/// A request and the response Teamwork gave to it.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Exchange {
+    pub request: ApiRequest,
+    pub response: ApiResponse,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request could not be sent, or its response read
+    Http(reqwest::Error),
+    /// Teamwork answered with an error status
+    Status(u16, serde_json::Value),
+    /// The response is not the JSON expected
+    Json(serde_json::Error),
+    /// No recorded exchange matches the request
+    NoFixture(ApiRequest),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            ApiError::Http(e) => write!(f, "{}", e),
+            ApiError::Status(status, body) => write!(f, "Teamwork answered {} : {}", status, body),
+            ApiError::Json(e) => write!(f, "Unexpected response : {}", e),
+            ApiError::NoFixture(r) => write!(f, "No recorded response to {} {}", r.method, r.path),
+        };
+    }
+}
+
+impl Error for ApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            ApiError::Http(e) => Some(e),
+            ApiError::Json(e) => Some(e),
+            _ => None,
+        };
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        return ApiError::Http(e);
+    }
+}
+
+impl From<serde_json::Error> for ApiError {
+    fn from(e: serde_json::Error) -> Self {
+        return ApiError::Json(e);
+    }
+}
+
+/// How requests reach Teamwork : over HTTP, or answered from recorded exchanges.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError>;
+
+    /// Sends the request without blocking. Sends it right away by default.
+    fn send_async(&self, request: ApiRequest) -> SendFuture {
+        return Box::new(future::result(self.send(&request)));
+    }
+}
+
+/// Transport of the requests to `https://<company>.eu.teamwork.com`, authenticated with the token.
+/// Its clones share their connection pools.
+#[derive(Clone)]
+pub struct HttpTransport {
+    http: reqwest::Client,
+    async_http: reqwest::r#async::Client,
+    base_url: String,
+    token: String,
+}
+
+impl HttpTransport {
+    pub fn new(company_id: &str, token: &str) -> HttpTransport {
+        return HttpTransport {
+            http: reqwest::Client::new(),
+            async_http: reqwest::r#async::Client::new(),
+            base_url: format!("https://{}.eu.teamwork.com", company_id),
+            token: token.to_string(),
+        };
+    }
+
+    fn url(&self, request: &ApiRequest) -> String {
+        return format!("{}/{}", self.base_url, request.path);
+    }
+
+    /// Logs the request and returns its URL with the query, to log the response along.
+    fn log_request(&self, request: &ApiRequest) -> String {
+        let query = request.query.iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>();
+        let url = match query.is_empty() {
+            true => self.url(request),
+            false => format!("{}?{}", self.url(request), query.join("&")),
+        };
+        let url = redact(&url, &self.token);
+
+        debug!("{} {}", request.method, url);
+        if let Some(body) = &request.body {
+            trace!("Request body : {}", redact(&body.to_string(), &self.token));
+        }
+
+        return url;
+    }
+
+    /// Logs the status, the time taken, the rate limit and, at trace level, the body of the response.
+    fn log_response(&self, method: &str, url: &str, started: Instant, result: &Result<ApiResponse, ApiError>) {
+        let elapsed = started.elapsed().as_millis();
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                debug!("{} {} failed after {}ms : {}", method, url, elapsed, redact(&e.to_string(), &self.token));
+                return;
+            }
+        };
+
+        debug!("{} {} -> {} in {}ms", method, url, response.status, elapsed);
+        let rate_limit = response.headers.iter()
+            .filter(|(k, _)| k.starts_with("x-ratelimit"))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>();
+        if !rate_limit.is_empty() {
+            debug!("Rate limit : {}", rate_limit.join(", "));
+        }
+        if log_enabled!(Level::Trace) {
+            trace!("Response body : {}", redact(&response.body.to_string(), &self.token));
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError> {
+        let method = Method::from_bytes(request.method.as_bytes()).unwrap_or(Method::GET);
+        let no_password: Option<String> = None;
+
+        let mut builder = self.http.request(method, self.url(request).as_str())
+            .basic_auth(&self.token, no_password)
+            .query(&request.query);
+        if let Some(body) = &request.body {
+            builder = builder.body(body.to_string());
+        }
+
+        let url = self.log_request(request);
+        let started = Instant::now();
+        let result = builder.send()
+            .and_then(|mut response| {
+                let text = response.text()?;
+                Ok(ApiResponse::read(response.status(), response.headers(), text))
+            })
+            .map_err(ApiError::from);
+        self.log_response(&request.method, &url, started, &result);
+
+        return result;
+    }
+
+    fn send_async(&self, request: ApiRequest) -> SendFuture {
+        let method = Method::from_bytes(request.method.as_bytes()).unwrap_or(Method::GET);
+        let no_password: Option<String> = None;
+
+        let mut builder = self.async_http.request(method, self.url(&request).as_str())
+            .basic_auth(&self.token, no_password)
+            .query(&request.query);
+        if let Some(body) = &request.body {
+            builder = builder.body(body.to_string());
+        }
+
+        let url = self.log_request(&request);
+        let started = Instant::now();
+        let transport = self.clone();
+        return Box::new(builder.send()
+            .and_then(|mut response| {
+                let status = response.status();
+                let headers = response.headers().clone();
+                response.text().map(move |text| ApiResponse::read(status, &headers, text))
+            })
+            .map_err(ApiError::from)
+            .then(move |result| {
+                transport.log_response(&request.method, &url, started, &result);
+                result
+            }));
+    }
+}
+
+/// Answers the requests from recorded exchanges, never reaching Teamwork.
+///
+/// A request matches an exchange with the same method and path, and at least its query parameters.
+/// The requests received are kept, to check what would have been sent.
+pub struct ReplayTransport {
+    exchanges: Vec<Exchange>,
+    requests: Mutex<Vec<ApiRequest>>,
+}
+
+impl ReplayTransport {
+    pub fn new(exchanges: Vec<Exchange>) -> ReplayTransport {
+        let exchanges = exchanges.into_iter()
+            .map(|e| Exchange {
+                request: ApiRequest::new(&e.request.method, &e.request.path)
+                    .with_query(&e.request.query.into_iter().collect::<Vec<_>>()),
+                response: ApiResponse {
+                    headers: e.response.headers.into_iter().map(|(k, v)| (k.to_lowercase(), v)).collect(),
+                    ..e.response
+                },
+            })
+            .collect();
+
+        return ReplayTransport { exchanges, requests: Mutex::new(vec![]) };
+    }
+
+    /// Reads the exchanges from a JSON file, as written by a [`RecordingTransport`].
+    pub fn from_file(path: &Path) -> Result<ReplayTransport, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let exchanges: Vec<Exchange> = serde_json::from_str(&content)?;
+
+        return Ok(ReplayTransport::new(exchanges));
+    }
+
+    pub fn requests(&self) -> Vec<ApiRequest> {
+        return self.requests.lock().unwrap().clone();
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError> {
+        self.requests.lock().unwrap().push(request.clone());
+
+        return self.exchanges.iter()
+            .find(|e| e.request.method.eq_ignore_ascii_case(&request.method)
+                && e.request.path == request.path
+                && e.request.query.iter().all(|(k, v)| request.query.get(k) == Some(v)))
+            .map(|e| e.response.clone())
+            .ok_or_else(|| ApiError::NoFixture(request.clone()));
+    }
+}
+
+/// Sends the requests through another transport, and writes every exchange to a JSON file
+/// which can be replayed with a [`ReplayTransport`].
+pub struct RecordingTransport {
+    inner: Box<dyn Transport>,
+    path: PathBuf,
+    exchanges: Mutex<Vec<Exchange>>,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Box<dyn Transport>, path: &Path) -> RecordingTransport {
+        return RecordingTransport {
+            inner,
+            path: path.to_path_buf(),
+            exchanges: Mutex::new(vec![]),
+        };
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn send(&self, request: &ApiRequest) -> Result<ApiResponse, ApiError> {
+        let response = self.inner.send(request)?;
+
+        let mut exchanges = self.exchanges.lock().unwrap();
+        exchanges.push(Exchange { request: request.clone(), response: response.clone() });
+        let content = serde_json::to_string_pretty(&*exchanges)?;
+        if let Err(e) = fs::write(&self.path, content) {
+            warn!("Could not record exchanges in {} : {}", self.path.display(), e);
+        }
+
+        return Ok(response);
+    }
+}
+
+/// Transport of a configuration, replaying or recording exchanges when `TEAMWORK_REPLAY`
+/// or `TEAMWORK_RECORD` is set.
+pub fn transport_from_env(config: &TeamWorkConfig) -> Arc<dyn Transport> {
+    let http = HttpTransport::new(&config.company_id, &config.token);
+
+    if let Ok(path) = env::var(REPLAY_ENV) {
+        return match ReplayTransport::from_file(Path::new(&path)) {
+            Ok(replay) => Arc::new(replay),
+            Err(e) => panic!("Could not read the exchanges to replay from {} : {}", path, e),
+        };
+    }
+    if let Ok(path) = env::var(RECORD_ENV) {
+        return Arc::new(RecordingTransport::new(Box::new(http), Path::new(&path)));
+    }
+
+    return Arc::new(http);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_record_then_replay() {
+        let teamwork = ReplayTransport::new(vec![Exchange {
+            request: ApiRequest::new("GET", "time_entries.json").with_query(&[("page", "2")]),
+            response: ApiResponse {
+                status: 200,
+                headers: vec![("X-Pages".to_string(), "2".to_string())].into_iter().collect(),
+                body: json!({ "STATUS": "OK" }),
+            },
+        }]);
+
+        let path = env::temp_dir().join(format!("teamwork-record-{}.json", std::process::id()));
+        let recording = RecordingTransport::new(Box::new(teamwork), &path);
+
+        let request = ApiRequest::new("GET", "/time_entries.json").with_query(&[("page", "2"), ("pageSize", "500")]);
+        let recorded = recording.send(&request).unwrap();
+        assert_eq!(recorded.header("X-Pages"), Some("2"));
+        assert!(recording.send(&ApiRequest::new("GET", "time_entries.json")).is_err());
+
+        let replay = ReplayTransport::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.send(&request).unwrap(), recorded);
+        match replay.send(&ApiRequest::new("POST", "time_entries.json")) {
+            Err(ApiError::NoFixture(r)) => assert_eq!(r.method, "POST"),
+            r => panic!("Expected no fixture, got {:?}", r),
+        }
+        assert_eq!(replay.requests().len(), 2);
+    }
+}
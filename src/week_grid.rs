@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::slice::Iter;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::parsing::parse_time_duration;
+use crate::teamwork_config::TimeOff;
+use crate::teamwork_service::{expected_workload, minutes_to_hours, Task, TimeEntry};
+
+/// Hours typed for some tasks on each day of a week, to be posted in a single batch.
+pub struct WeekGrid {
+    pub week_start: NaiveDate,
+    pub rows: Vec<GridRow>,
+    /// Hours already logged on each task for each day, by task name.
+    pub logged_by_task: BTreeMap<String, [i32; 7]>,
+    /// Hours already logged on each day.
+    pub logged: [i32; 7],
+    /// Hours expected on each day.
+    pub expected: [i32; 7],
+}
+
+pub struct GridRow {
+    pub task: Task,
+    pub hours: [i32; 7],
+}
+
+impl WeekGrid {
+    pub fn new(
+        week_start: NaiveDate,
+        tasks: Vec<Task>,
+        existing_time_entries: &Iter<TimeEntry>,
+        times_off: &Iter<TimeOff>,
+    ) -> WeekGrid {
+        let minutes_by_task = logged_minutes_by_task(week_start, existing_time_entries);
+
+        // Rounded once summed, for the minutes of several entries to add up
+        let mut logged_minutes = [0; 7];
+        for minutes in minutes_by_task.values() {
+            for (day, m) in minutes.iter().enumerate() {
+                logged_minutes[day] += m;
+            }
+        }
+        let logged = logged_minutes.map(minutes_to_hours);
+        let logged_by_task = minutes_by_task.into_iter()
+            .map(|(task, minutes)| (task, minutes.map(minutes_to_hours)))
+            .collect();
+
+        let mut expected = [0; 7];
+        for (day, e) in expected.iter_mut().enumerate() {
+            *e = expected_workload(week_start + Duration::days(day as i64), times_off);
+        }
+
+        return WeekGrid {
+            week_start,
+            rows: tasks.into_iter().map(|task| GridRow { task, hours: [0; 7] }).collect(),
+            logged_by_task,
+            logged,
+            expected,
+        };
+    }
+
+    pub fn date(&self, day: usize) -> NaiveDate {
+        return self.week_start + Duration::days(day as i64);
+    }
+
+    /// Hours of each day, already logged ones included.
+    pub fn day_totals(&self) -> [i32; 7] {
+        let mut totals = self.logged;
+        for row in self.rows.iter() {
+            for (day, hours) in row.hours.iter().enumerate() {
+                totals[day] += hours;
+            }
+        }
+
+        return totals;
+    }
+
+    /// The (task, date, hours) of every filled cell.
+    pub fn entries(&self) -> Vec<(&Task, NaiveDate, i32)> {
+        return self.rows.iter()
+            .flat_map(|row| row.hours.iter().enumerate()
+                .filter(|(_, hours)| **hours > 0)
+                .map(move |(day, hours)| (&row.task, self.date(day), *hours)))
+            .collect();
+    }
+}
+
+/// Minutes logged on each task for each day of the week starting at `week_start`, by task name.
+fn logged_minutes_by_task(week_start: NaiveDate, time_entries: &Iter<TimeEntry>) -> BTreeMap<String, [i32; 7]> {
+    let mut logged: BTreeMap<String, [i32; 7]> = BTreeMap::new();
+
+    for e in time_entries.clone() {
+        let day = (e.date.date_naive() - week_start).num_days();
+        if !(0..7).contains(&day) {
+            continue;
+        }
+
+        // Entries logged on a project have no task name
+        let name = match e.todo_item_name.is_empty() {
+            true => e.project_name.clone(),
+            false => e.todo_item_name.clone(),
+        };
+        logged.entry(name).or_default()[day as usize] += e.minutes();
+    }
+
+    return logged;
+}
+
+/// Parses the hours of a row from Monday to Sunday, like `8 8 4h 0 -`. Missing days are 0.
+pub fn parse_row(input: &str) -> Result<[i32; 7], String> {
+    let cells = input.split_whitespace().collect::<Vec<_>>();
+    if cells.len() > 7 {
+        return Err(format!("Expected at most 7 days, got {}", cells.len()));
+    }
+
+    let mut hours = [0; 7];
+    for (day, cell) in cells.iter().enumerate() {
+        hours[day] = match *cell {
+            "-" | "." => 0,
+            c => parse_time_duration(c)
+                .ok_or_else(|| format!("Could not parse {} as a number of hours", c))?,
+        };
+    }
+
+    return Ok(hours);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_totals_and_entries() {
+        let task = |id: usize| Task {
+            id,
+            project_name: "Project".to_string(),
+            todo_list_name: "Task list".to_string(),
+            todo_list_id: None,
+            parent_task: None,
+            name: format!("Task {}", id),
+            sub_tasks: vec![],
+        };
+        let monday = NaiveDate::from_ymd_opt(2020, 1, 20).unwrap();
+        let times_off = [TimeOff { date: "2020-01-24".to_string(), hours: 4 }];
+
+        let mut grid = WeekGrid::new(monday, vec![task(1), task(2)], &[].iter(), &times_off.iter());
+        grid.rows[0].hours = parse_row("8 4 - 2h").unwrap();
+        grid.rows[1].hours = parse_row("0 4").unwrap();
+
+        assert_eq!(grid.expected, [8, 8, 8, 8, 4, 0, 0]);
+        assert_eq!(grid.day_totals(), [8, 8, 0, 2, 0, 0, 0]);
+        assert_eq!(grid.entries().iter().map(|(t, d, h)| (t.id, *d, *h)).collect::<Vec<_>>(), vec![
+            (1, monday, 8),
+            (1, monday + Duration::days(1), 4),
+            (1, monday + Duration::days(3), 2),
+            (2, monday + Duration::days(1), 4),
+        ]);
+        assert!(parse_row("8 8 8 8 8 8 8 8").is_err());
+        assert!(parse_row("8 eight").is_err());
+    }
+
+    #[test]
+    fn test_logged_hours_are_summed_by_task_and_day() {
+        let entry = |day: u32, hours: i32, task: &str| TimeEntry::logged_on(NaiveDate::from_ymd_opt(2020, 1, day).unwrap(), hours, task);
+        let entries = [
+            entry(19, 8, "Support"),
+            entry(20, 4, "Support"),
+            entry(20, 2, "Support"),
+            entry(22, 8, "Development"),
+            entry(23, 1, ""),
+            entry(27, 8, "Development"),
+        ];
+
+        let grid = WeekGrid::new(NaiveDate::from_ymd_opt(2020, 1, 20).unwrap(), vec![], &entries.iter(), &[].iter());
+
+        assert_eq!(grid.logged_by_task.len(), 3);
+        assert_eq!(grid.logged_by_task["Support"], [6, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(grid.logged_by_task["Development"], [0, 0, 8, 0, 0, 0, 0]);
+        assert_eq!(grid.logged_by_task["Project"], [0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(grid.day_totals(), [6, 0, 8, 1, 0, 0, 0]);
+    }
+}
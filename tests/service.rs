@@ -0,0 +1,209 @@
+#![allow(clippy::needless_return)]
+
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{NaiveDate, Utc};
+use serde_json::json;
+
+use teamwork_cli::teamwork_config::{TeamWorkConfig, TimeOff};
+use teamwork_cli::teamwork_service::{is_working_day, Billing, EntryTarget, TeamWorkService, TimeEntryInput, TimeInput};
+use teamwork_cli::cache::Cache;
+use teamwork_cli::queue::QueuedEntry;
+use teamwork_cli::transport::{ApiError, ReplayTransport};
+
+fn config() -> TeamWorkConfig {
+    return TeamWorkConfig {
+        company_id: "fixtures".to_string(),
+        token: "secret".to_string(),
+        // Nothing is read from the cache, every request reaches the fixtures
+        cache_ttl_minutes: Some(0),
+        ..Default::default()
+    };
+}
+
+/// Service answered from `tests/fixtures/<fixture>`, with no queued entry and a cache in memory.
+fn service(fixture: &str) -> (TeamWorkService, Arc<ReplayTransport>) {
+    return service_with(&config(), fixture);
+}
+
+fn service_with(config: &TeamWorkConfig, fixture: &str) -> (TeamWorkService, Arc<ReplayTransport>) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(fixture);
+    let replay = Arc::new(ReplayTransport::from_file(&path).unwrap());
+
+    return (TeamWorkService::with_transport(config, replay.clone()), replay);
+}
+
+fn date(day: u32) -> NaiveDate {
+    return NaiveDate::from_ymd_opt(2019, 7, day).unwrap();
+}
+
+#[test]
+fn test_save_time_tops_up_existing_entries() {
+    let (service, replay) = service("save_time.json");
+    let billing = Billing { is_billable: true, tags: vec!["on-site".to_string()] };
+
+    let input = TimeInput { target: EntryTarget::Task("123".to_string()), start_date: date(1), hours: 12, description: "Dev".to_string(), billing };
+
+    let saved_days = service.save_time(&input, false, &[].iter()).unwrap();
+
+    assert_eq!(saved_days.iter().map(|s| s.day.hours).sum::<i32>(), 12);
+    assert!(saved_days.iter().all(|s| s.response.as_ref().map(|r| r.status == "OK").unwrap_or(false)));
+    let posted: Vec<_> = replay.requests().into_iter()
+        .filter(|r| r.method == "POST")
+        .map(|r| (r.path, r.body.unwrap()))
+        .collect();
+    let body = |date: &str, hours: &str| json!({
+        "time-entry": {
+            "description": "Dev",
+            "person-id": "42",
+            "date": date,
+            "time": "08:00",
+            "hours": hours,
+            "minutes": "0",
+            "isbillable": true,
+            "tags": "on-site",
+        }
+    });
+    assert_eq!(posted, vec![
+        ("tasks/123/time_entries.json".to_string(), body("20190701", "4")),
+        ("tasks/123/time_entries.json".to_string(), body("20190702", "8")),
+    ]);
+}
+
+#[test]
+fn test_posted_entries_are_billable_on_billable_projects() {
+    let config = TeamWorkConfig { billable_projects: vec!["7".to_string()], ..config() };
+    let (service, replay) = service_with(&config, "save_time.json");
+
+    let time_entry = TimeEntryInput::new(date(3), 2, "Review", "42");
+    service.post_entry(&EntryTarget::Task("123".to_string()), &time_entry).unwrap();
+
+    let posted: Vec<_> = replay.requests().into_iter()
+        .filter(|r| r.method == "POST")
+        .map(|r| r.body.unwrap())
+        .collect();
+    assert_eq!(posted.len(), 1);
+    assert_eq!(posted[0]["time-entry"]["isbillable"], json!(true));
+}
+
+#[test]
+fn test_save_time_dry_run_posts_nothing() {
+    let (service, replay) = service("save_time.json");
+
+    let input = TimeInput { target: EntryTarget::Task("123".to_string()), start_date: date(1), hours: 12, description: "Dev".to_string(), billing: Billing::default() };
+
+    service.save_time(&input, true, &[].iter()).unwrap();
+
+    assert!(replay.requests().iter().all(|r| r.method == "GET"));
+}
+
+#[test]
+fn test_missing_entries_subtract_time_entries_and_times_off() {
+    let (service, _) = service("missing_time.json");
+    let times_off = [TimeOff { date: "2019-07-03".to_string(), hours: 8 }];
+
+    let missing = service.get_missing_entries(date(1), &times_off.iter()).unwrap();
+
+    let today = Utc::now().date_naive();
+    let working_days = date(1).iter_days()
+        .take_while(|d| d < &today)
+        .filter(|d| is_working_day(*d))
+        .count() as i32;
+    assert_eq!(missing, working_days * 8 - 8 - 6 - 8);
+}
+
+#[test]
+fn test_queued_entries_are_counted_as_logged() {
+    let (service, _) = service("missing_time.json");
+    let queued = QueuedEntry {
+        id: 1,
+        task_id: "123".to_string(),
+        project_id: None,
+        entry: TimeEntryInput::new(date(4), 5, "Offline", ""),
+        queued_at: chrono::Local::now(),
+        attempts: 0,
+        last_error: None,
+    };
+
+    let without_queue = service.get_missing_entries(date(1), &[].iter()).unwrap();
+    let with_queue = service.with_queued_entries(vec![queued.as_time_entry()])
+        .get_missing_entries(date(1), &[].iter())
+        .unwrap();
+
+    assert_eq!(without_queue - with_queue, 5);
+}
+
+#[test]
+fn test_pages_are_all_read() {
+    let (service, replay) = service("pagination.json");
+
+    let entries = service.all_time_entries(date(1)).unwrap();
+
+    assert_eq!(entries.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["1", "2", "3", "4"]);
+    assert_eq!(replay.requests().len(), 3);
+}
+
+#[test]
+fn test_pages_without_count_stop_on_the_last_one() {
+    let (service, replay) = service("pagination.json");
+
+    let logged_time = service.project_logged_time("7").unwrap();
+
+    assert_eq!(logged_time.iter().map(|t| t.minutes()).sum::<i64>(), 210);
+    assert_eq!(replay.requests().len(), 1);
+}
+
+#[test]
+fn test_completed_task_is_removed_from_the_cached_task_list() {
+    let (service, replay) = service("tasks.json");
+    let service = service.with_cache(Cache::in_memory("fixtures", 60));
+    let tasklist_requests = || replay.requests().iter().filter(|r| r.path == "tasklists/70/tasks.json").count();
+
+    service.list_task("70").unwrap();
+    service.list_task("70").unwrap();
+    assert_eq!(tasklist_requests(), 1);
+
+    service.complete_task("123").unwrap();
+    service.list_task("70").unwrap();
+    assert_eq!(tasklist_requests(), 2);
+}
+
+#[test]
+fn test_unreadable_tasks_are_skipped() {
+    let (service, _) = service("tasks.json");
+
+    let fetched = service.get_tasks(&[404, 123]).unwrap();
+
+    assert_eq!(fetched.tasks.iter().map(|t| t.id).collect::<Vec<_>>(), vec![123]);
+    match fetched.failures.as_slice() {
+        [(404, ApiError::Status(404, _))] => {}
+        f => panic!("Expected task 404 to fail, got {:?}", f),
+    }
+}
+
+#[test]
+fn test_task_logged_minutes_read_every_page() {
+    let (service, replay) = service("tasks.json");
+
+    assert_eq!(service.get_task_logged_minutes("123").unwrap(), 255);
+    assert_eq!(replay.requests().len(), 2);
+}
+
+#[test]
+fn test_errors_are_reported() {
+    let (service, _) = service("errors.json");
+
+    match service.list_project(&None) {
+        Err(ApiError::Status(500, body)) => assert_eq!(body, json!({ "MESSAGE": "Internal error" })),
+        r => panic!("Expected a status error, got {:?}", r.map(|p| p.projects.len())),
+    }
+    match service.get_account() {
+        Err(ApiError::Json(_)) => {}
+        r => panic!("Expected a json error, got {:?}", r),
+    }
+    match service.get_task(&1) {
+        Err(ApiError::NoFixture(request)) => assert_eq!(request.path, "tasks/1.json"),
+        r => panic!("Expected no fixture, got {:?}", r),
+    }
+}